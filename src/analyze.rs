@@ -1,10 +1,15 @@
-use crate::{builder::builder::Builder, cmake, config::Config};
+use crate::{
+    builder::{builder::Builder, doctest::check_examples},
+    cmake,
+    config::{ClangSeverity, Config, OnErrors},
+};
+use clang::{diagnostic::Severity, TranslationUnit};
 use indicatif::{ProgressBar, ProgressStyle};
-use std::{fs, path::PathBuf, process::Command, sync::Arc, time::Duration};
+use std::{fs, io, path::{Path, PathBuf}, process::Command, sync::Arc, time::Duration};
 
 fn run_command(cmd: &String) -> Result<(), String> {
-    let args =
-        shlex::split(cmd).unwrap_or_else(|| panic!("Unable to parse prebuild command `{cmd}`"));
+    let args = shlex::split(cmd)
+        .ok_or_else(|| format!("Unable to parse prebuild command `{cmd}`"))?;
     let exit = Command::new(&args[0])
         .args(&args[1..])
         .spawn()
@@ -18,6 +23,25 @@ fn run_command(cmd: &String) -> Result<(), String> {
     }
 }
 
+/// Remove everything in a directory except `keep`
+fn remove_dir_contents(path: &Path, keep: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path == keep {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            remove_dir_contents(&path, keep)?;
+            fs::remove_dir(path)?;
+        } else {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
 fn create_analyzable_file(config: Arc<Config>) -> Result<PathBuf, String> {
     let out_path = config.output_dir.join("_analyze.cpp");
 
@@ -34,6 +58,64 @@ fn create_analyzable_file(config: Arc<Config>) -> Result<PathBuf, String> {
     Ok(out_path)
 }
 
+/// Report the diagnostics libclang produced while parsing the headers. Errors
+/// usually mean include paths or compile args are wrong, so the docs would be
+/// missing things
+fn report_clang_diagnostics(config: &Config, unit: &TranslationUnit) -> Result<(), String> {
+    let fail = config.analysis.on_errors == OnErrors::Fail;
+    let mut errors = 0;
+    for diag in unit.get_diagnostics() {
+        let severity = diag.get_severity();
+        let is_error = severity >= Severity::Error;
+        if is_error {
+            errors += 1;
+        }
+        let level = match severity {
+            Severity::Ignored => continue,
+            Severity::Note => ClangSeverity::Note,
+            Severity::Warning => ClangSeverity::Warning,
+            Severity::Error | Severity::Fatal => ClangSeverity::Error,
+        };
+        if level < config.analysis.min_severity {
+            continue;
+        }
+
+        let loc = diag.get_location().get_file_location();
+        let location = loc.file.map(|file| {
+            let path = file.get_path();
+            format!(
+                "{}:{}:{}",
+                path.strip_prefix(&config.input_dir).unwrap_or(&path).to_string_lossy(),
+                loc.line,
+                loc.column,
+            )
+        });
+        if is_error && fail {
+            config.diagnostics.error("clang", location, diag.get_text());
+        }
+        else {
+            config.diagnostics.warn("clang", location, diag.get_text());
+        }
+    }
+
+    if errors > 0 {
+        if fail {
+            return Err(format!(
+                "libclang reported {errors} errors while parsing the headers"
+            ));
+        }
+        config.diagnostics.warn(
+            "clang",
+            None,
+            format!(
+                "libclang reported {errors} errors while parsing the headers, \
+                so the docs may be incomplete"
+            ),
+        );
+    }
+    Ok(())
+}
+
 async fn analyze_with_clang(config: Arc<Config>, args: &[String]) -> Result<(), String> {
     // Initialize clang
     let clang = clang::Clang::new()?;
@@ -66,10 +148,26 @@ async fn analyze_with_clang(config: Arc<Config>, args: &[String]) -> Result<(),
     // Create parser
     let unit = index.parser(&target_src).arguments(args).parse()?;
 
+    // Stop before any output is written if the headers are broken
+    if let Err(e) = report_clang_diagnostics(&config, &unit) {
+        pbar.finish_and_clear();
+        drop(fs::remove_file(target_src));
+        return Err(e);
+    }
+
+    // Only now replace the docs from the previous build
+    remove_dir_contents(&config.output_dir, &target_src)
+        .map_err(|e| format!("Unable to clear output directory: {e}"))?;
+
     // Build the navbar first
     pbar.set_message("Setting up");
     let builder = Builder::new(config, unit.get_entity(), &clang, &index, args)?;
 
+    if builder.config.cli.check_examples {
+        pbar.set_message("Checking examples");
+        check_examples(&builder);
+    }
+
     // Build the doc files
     pbar.set_message("Building docs");
     builder.build(Some(pbar.clone())).await?;
@@ -109,7 +207,8 @@ async fn analyze_with_cmake(config: Arc<Config>) -> Result<(), String> {
 
     analyze_with_clang(
         config.clone(),
-        &cmake::cmake_compile_args_for(config).expect("Unable to infer CMake compile args"),
+        &cmake::cmake_compile_args_for(config)
+            .map_err(|e| format!("Unable to infer CMake compile args: {e}"))?,
     )
     .await?;
 
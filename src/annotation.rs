@@ -1,8 +1,26 @@
 
 use std::ops::Range;
 
+/// Length of the code span at the start of `raw`, or just the length of the
+/// opening backticks if they are never closed
+pub fn code_span_len(raw: &str) -> usize {
+    let ticks = raw.len() - raw.trim_start_matches('`').len();
+    let fence = &raw[..ticks];
+    let mut search = ticks;
+    while let Some(pos) = raw[search..].find(fence) {
+        let start = search + pos;
+        let run = raw[start..].len() - raw[start..].trim_start_matches('`').len();
+        if run == ticks {
+            return start + run;
+        }
+        search = start + run;
+    }
+    ticks
+}
+
 struct Annotation {
     raw: String,
+    /// Byte range of the word in the original string
     range: Range<usize>,
     value: Option<String>,
 }
@@ -24,42 +42,23 @@ impl<'a> Annotations<'a> {
     }
 
     pub fn into_result(self) -> String {
-        let mut result = String::from(self.raw);
-        let mut offset = 0isize;
+        let mut result = String::with_capacity(self.raw.len());
+        let mut last = 0;
         for word in self.annotations {
             if let Some(value) = word.value {
-                result.replace_range(
-                    (word.range.start as isize + offset) as usize
-                    ..(word.range.end as isize + offset) as usize,
-                    &value
-                );
-                // Applying this annotation may cause the next annotations to 
-                // shifted if the replaced string is shorter / longer than the 
-                // original
-                offset += value.len() as isize - word.raw.len() as isize;
+                result.push_str(&self.raw[last..word.range.start]);
+                result.push_str(&value);
+                last = word.range.end;
             }
         }
+        result.push_str(&self.raw[last..]);
         result
     }
 
-    pub fn rewind(&mut self) {
-        self.next_in_iter = 0;
-    }
-
     pub fn next(&mut self) -> Option<String> {
-        self.annotations.iter()
-            .skip(self.next_in_iter)
-            .find(|a| {
-                if a.value.is_some() {
-                    self.next_in_iter += 1;
-                    true
-                }
-                else {
-                    false
-                }
-            })
-            .inspect(|_| self.next_in_iter += 1)
-            .map(|a| a.raw.clone())
+        let word = self.annotations.get(self.next_in_iter)?;
+        self.next_in_iter += 1;
+        Some(word.raw.clone())
     }
 
     pub fn annotate(&mut self, value: String) {
@@ -67,20 +66,27 @@ impl<'a> Annotations<'a> {
     }
 
     fn skip_to_next_word(raw: &'a str, iter_ix: &mut usize) {
-        while let Some(i) = raw.chars().nth(*iter_ix) && !i.is_alphanumeric() {
-            *iter_ix += 1;
+        while let Some(i) = raw[*iter_ix..].chars().next() && !i.is_alphanumeric() {
+            *iter_ix += match i {
+                // Words in code spans and link destinations should be left 
+                // as-is
+                '`' => code_span_len(&raw[*iter_ix..]),
+                ']' if raw[*iter_ix..].starts_with("](") => raw[*iter_ix..]
+                    .find(')')
+                    .map(|p| p + 1)
+                    .unwrap_or(1),
+                _ => i.len_utf8(),
+            };
         }
     }
 
     fn next_word(raw: &'a str, iter_ix: &mut usize) -> Option<(Range<usize>, String)> {
         let start = *iter_ix;
-        let res: String = raw.chars()
-            .skip(*iter_ix)
-            .take_while(|c| c.is_alphanumeric())
-            .collect();
-        *iter_ix += res.len();
-        let end = *iter_ix;
-        (!res.is_empty()).then_some((start..end, res))
+        let len = raw[start..]
+            .find(|c: char| !c.is_alphanumeric())
+            .unwrap_or(raw.len() - start);
+        *iter_ix += len;
+        (len > 0).then(|| (start..*iter_ix, raw[start..*iter_ix].to_owned()))
     }
 
     fn next_annotation(raw: &'a str, iter_ix: &mut usize) -> Option<Annotation> {
@@ -0,0 +1,601 @@
+use std::{collections::BTreeMap, fs, path::Path, sync::Arc};
+
+use clang::{Accessibility, Entity, EntityKind};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    diagnostics::OutputFormat,
+    html::{Html, HtmlElement, HtmlText},
+    url::UrlPath,
+};
+
+use super::{
+    builder::Builder,
+    namespace::CppItem,
+    resolve::Target,
+    traits::{ASTEntry, Access, BuildResult, EntityMethods, Entry, Include, NavItem, OutputEntry},
+};
+
+/// The public API surface of a single entity, as far as it matters for
+/// finding breaking changes
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiEntity {
+    kind: String,
+    /// Return type for functions and type for fields
+    #[serde(rename = "type")]
+    ty: Option<String>,
+    /// Parameter types for functions
+    params: Option<Vec<String>>,
+    bases: Vec<String>,
+    is_static: bool,
+    is_virtual: bool,
+    is_pure_virtual: bool,
+    is_const: bool,
+    is_deprecated: bool,
+    /// Where the entity is documented in the build the model is from
+    url: Option<String>,
+}
+
+impl ApiEntity {
+    fn new(entity: &Entity, kind: &str, builder: &Builder) -> Self {
+        let display = |ty: clang::Type| ty.get_display_name();
+        let is_fun = matches!(kind, "function" | "member function");
+        Self {
+            kind: kind.to_string(),
+            ty: if is_fun {
+                entity.get_result_type().map(display)
+            }
+            else if kind == "field" {
+                entity.get_type().map(display)
+            }
+            else {
+                None
+            },
+            params: entity.get_function_arguments().map(|args| {
+                args.iter()
+                    .map(|arg| arg.get_type().map(display).unwrap_or_default())
+                    .collect()
+            }),
+            bases: entity
+                .get_children()
+                .iter()
+                .filter(|child| child.get_kind() == EntityKind::BaseSpecifier)
+                .map(|base| base.get_display_name().unwrap_or_default())
+                .collect(),
+            is_static: is_fun && entity.is_static_method(),
+            is_virtual: is_fun && entity.is_virtual_method(),
+            is_pure_virtual: is_fun && entity.is_pure_virtual_method(),
+            is_const: is_fun && entity.is_const_method(),
+            is_deprecated: entity.is_deprecated(),
+            url: Target::of(entity).and_then(|t| t.href(builder.config.clone())),
+        }
+    }
+
+    /// The entity's declaration as a single line, like
+    /// `virtual int get(float) const`
+    fn signature(&self, name: &str) -> String {
+        let mut res = String::new();
+        if self.is_static {
+            res += "static ";
+        }
+        if self.is_virtual {
+            res += "virtual ";
+        }
+        if let Some(ref ty) = self.ty {
+            res += &format!("{ty} ");
+        }
+        res += name;
+        if let Some(ref params) = self.params {
+            res += &format!("({})", params.join(", "));
+        }
+        if self.is_const {
+            res += " const";
+        }
+        if self.is_pure_virtual {
+            res += " = 0";
+        }
+        if !self.bases.is_empty() {
+            res += &format!(" : {}", self.bases.join(", "));
+        }
+        res
+    }
+
+    /// Describe how this entity changed into `new`
+    fn changes_to(&self, new: &ApiEntity) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.kind != new.kind {
+            changes.push(format!("Changed from {} to {}", self.kind, new.kind));
+        }
+        if self.ty != new.ty {
+            let what = if new.kind == "field" { "Type" } else { "Return type" };
+            changes.push(format!(
+                "{what} changed from `{}` to `{}`",
+                self.ty.clone().unwrap_or_default(),
+                new.ty.clone().unwrap_or_default(),
+            ));
+        }
+        match (&self.params, &new.params) {
+            (Some(old), Some(new)) if old.len() == new.len() => {
+                for (i, (old, new)) in old.iter().zip(new).enumerate() {
+                    if old != new {
+                        changes.push(format!("Parameter {} changed from `{old}` to `{new}`", i + 1));
+                    }
+                }
+            }
+            (old, new) if old != new => changes.push(format!(
+                "Parameters changed from `({})` to `({})`",
+                old.clone().unwrap_or_default().join(", "),
+                new.clone().unwrap_or_default().join(", "),
+            )),
+            _ => {}
+        }
+        if self.bases != new.bases {
+            changes.push(format!(
+                "Base classes changed from `{}` to `{}`",
+                self.bases.join(", "),
+                new.bases.join(", "),
+            ));
+        }
+        for (old, new, what) in [
+            (self.is_static, new.is_static, "static"),
+            (self.is_virtual, new.is_virtual, "virtual"),
+            (self.is_pure_virtual, new.is_pure_virtual, "pure virtual"),
+            (self.is_const, new.is_const, "const"),
+            (self.is_deprecated, new.is_deprecated, "deprecated"),
+        ] {
+            if old != new {
+                changes.push(format!("Is {} {what}", if new { "now" } else { "no longer" }));
+            }
+        }
+        changes
+    }
+}
+
+/// The public API of a build, saved as `api.json` in the output so later
+/// builds can be compared against it
+#[derive(Serialize, Deserialize)]
+pub struct ApiModel {
+    project: String,
+    version: String,
+    /// Entities by their full name. Overloads share a name
+    entities: BTreeMap<String, Vec<ApiEntity>>,
+}
+
+impl ApiModel {
+    fn add(&mut self, entity: &Entity, kind: &str, builder: &Builder) {
+        self.entities
+            .entry(entity.full_name().join("::"))
+            .or_default()
+            .push(ApiEntity::new(entity, kind, builder));
+    }
+
+    fn collect(&mut self, item: &CppItem, builder: &Builder) {
+        match item {
+            CppItem::Namespace(ns) => {
+                for entry in ns.entries.values() {
+                    self.collect(entry, builder);
+                }
+            }
+            CppItem::Class(_) | CppItem::Struct(_) => {
+                let kind = if matches!(item, CppItem::Class(_)) { "class" } else { "struct" };
+                self.add(item.entity(), kind, builder);
+                for fun in item.entity().get_member_functions(Access::Public, Include::All) {
+                    self.add(&fun, "member function", builder);
+                }
+                for field in item.entity().get_children().iter().filter(|child| {
+                    child.get_kind() == EntityKind::FieldDecl
+                        && child.get_accessibility() == Some(Accessibility::Public)
+                }) {
+                    self.add(field, "field", builder);
+                }
+            }
+            CppItem::Function(_) => self.add(item.entity(), "function", builder),
+        }
+    }
+
+    pub fn from_builder(builder: &Builder) -> Self {
+        let mut model = Self {
+            project: builder.config.project.name.clone(),
+            version: builder.config.project.version.clone(),
+            entities: BTreeMap::new(),
+        };
+        for item in builder.root.entries.values() {
+            model.collect(item, builder);
+        }
+        model
+    }
+
+    /// Load the model of an earlier build from its output directory or its
+    /// `api.json`
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let path = if path.is_dir() { path.join("api.json") } else { path.to_path_buf() };
+        serde_json::from_str(
+            &fs::read_to_string(&path)
+                .map_err(|e| format!("Unable to read {}: {e}", path.display()))?,
+        )
+        .map_err(|e| format!("Unable to parse {}: {e}", path.display()))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        fs::write(
+            dir.join("api.json"),
+            serde_json::to_string(self).map_err(|e| format!("Unable to serialize API: {e}"))?,
+        )
+        .map_err(|e| format!("Unable to save api.json: {e}"))
+    }
+}
+
+#[derive(Serialize)]
+struct DiffItem {
+    name: String,
+    kind: String,
+    signature: String,
+    url: Option<String>,
+}
+
+impl DiffItem {
+    fn new(name: &str, entity: &ApiEntity) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: entity.kind.clone(),
+            signature: entity.signature(name),
+            url: entity.url.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ChangedItem {
+    name: String,
+    kind: String,
+    old: String,
+    new: String,
+    changes: Vec<String>,
+    url: Option<String>,
+}
+
+/// The differences between the APIs of two builds
+#[derive(Serialize)]
+pub struct ApiDiff {
+    project: String,
+    old_version: String,
+    new_version: String,
+    added: Vec<DiffItem>,
+    removed: Vec<DiffItem>,
+    changed: Vec<ChangedItem>,
+}
+
+impl ApiDiff {
+    pub fn new(old: &ApiModel, new: &ApiModel) -> Self {
+        let mut diff = Self {
+            project: new.project.clone(),
+            old_version: old.version.clone(),
+            new_version: new.version.clone(),
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        };
+        let none = Vec::new();
+        for (name, old_list) in &old.entities {
+            diff.compare(name, old_list, new.entities.get(name).unwrap_or(&none));
+        }
+        for (name, new_list) in &new.entities {
+            if !old.entities.contains_key(name) {
+                diff.added.extend(new_list.iter().map(|e| DiffItem::new(name, e)));
+            }
+        }
+        diff
+    }
+
+    /// Compare the overloads of a name. Overloads with the same parameters
+    /// and qualifiers are compared with each other first, then ones with just
+    /// the same parameters, so adding `get() const` next to `get()` doesn't
+    /// look like `get()` became const. If a single overload is left on both
+    /// sides its signature is considered changed rather than the overload
+    /// removed and another one added
+    fn compare(&mut self, name: &str, old: &[ApiEntity], new: &[ApiEntity]) {
+        let mut new = new.iter().collect::<Vec<_>>();
+        let mut old_left = old.iter().collect::<Vec<_>>();
+        for same_qualifiers in [true, false] {
+            old_left.retain(|old| {
+                let matching = new.iter().position(|n| {
+                    n.params == old.params
+                        && n.kind == old.kind
+                        && (!same_qualifiers
+                            || (n.is_const == old.is_const && n.is_static == old.is_static))
+                });
+                match matching {
+                    Some(i) => {
+                        self.add_changed(name, old, new.remove(i));
+                        false
+                    }
+                    None => true,
+                }
+            });
+        }
+        if let ([old], [new]) = (old_left.as_slice(), new.as_slice()) {
+            self.add_changed(name, old, new);
+            return;
+        }
+        self.removed.extend(old_left.into_iter().map(|e| DiffItem::new(name, e)));
+        self.added.extend(new.into_iter().map(|e| DiffItem::new(name, e)));
+    }
+
+    fn add_changed(&mut self, name: &str, old: &ApiEntity, new: &ApiEntity) {
+        let changes = old.changes_to(new);
+        if !changes.is_empty() {
+            self.changed.push(ChangedItem {
+                name: name.to_string(),
+                kind: new.kind.clone(),
+                old: old.signature(name),
+                new: new.signature(name),
+                changes,
+                url: new.url.clone(),
+            });
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "{} added, {} removed and {} changed between {} and {}",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len(),
+            self.old_version,
+            self.new_version,
+        )
+    }
+
+    fn print_text(&self) {
+        if self.is_empty() {
+            println!(
+                "No API changes in {} between {} and {}",
+                self.project, self.old_version, self.new_version
+            );
+            return;
+        }
+        println!("Changes in {}: {}", self.project, self.summary());
+        for item in &self.added {
+            println!("+ {} {}", item.kind, item.signature);
+        }
+        for item in &self.removed {
+            println!("- {} {}", item.kind, item.signature);
+        }
+        for item in &self.changed {
+            println!("~ {} {}", item.kind, item.name);
+            for change in &item.changes {
+                println!("    {change}");
+            }
+        }
+    }
+
+    fn fmt_name(name: &str, url: &Option<String>) -> Html {
+        match url {
+            Some(url) => HtmlElement::new("a")
+                .with_attr("href", url)
+                .with_attr("onclick", format!("return navigate('{url}')"))
+                .with_child(HtmlElement::new("code").with_text(name))
+                .into(),
+            None => HtmlElement::new("code").with_text(name).into(),
+        }
+    }
+
+    fn fmt_items(items: &[DiffItem], link: bool) -> Html {
+        HtmlElement::new("ul")
+            .with_class("changes")
+            .with_children(
+                items
+                    .iter()
+                    .map(|item| {
+                        HtmlElement::new("li")
+                            .with_child(if link {
+                                Self::fmt_name(&item.signature, &item.url)
+                            }
+                            else {
+                                HtmlElement::new("code").with_text(&item.signature).into()
+                            })
+                            .with_child(Html::span(&["kind"], &item.kind))
+                            .into()
+                    })
+                    .collect(),
+            )
+            .into()
+    }
+
+    fn fmt_changed(&self) -> Html {
+        HtmlElement::new("ul")
+            .with_class("changes")
+            .with_children(
+                self.changed
+                    .iter()
+                    .map(|item| {
+                        HtmlElement::new("li")
+                            .with_child(Self::fmt_name(&item.name, &item.url))
+                            .with_child(Html::span(&["kind"], &item.kind))
+                            .with_child(
+                                HtmlElement::new("ul").with_children(
+                                    item.changes
+                                        .iter()
+                                        .map(|change| HtmlElement::new("li").with_text(change).into())
+                                        .collect(),
+                                ),
+                            )
+                            .into()
+                    })
+                    .collect(),
+            )
+            .into()
+    }
+}
+
+impl<'e> Entry<'e> for ApiDiff {
+    fn name(&self) -> String {
+        String::from("Changes")
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::part("changes")
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        fs::write(
+            builder.config.output_dir.join("changes.json"),
+            serde_json::to_string_pretty(self)
+                .map_err(|e| format!("Unable to serialize changes: {e}"))?,
+        )
+        .map_err(|e| format!("Unable to save changes.json: {e}"))?;
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("layers", false)), Vec::new())
+    }
+}
+
+impl<'e> OutputEntry<'e> for ApiDiff {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+        (
+            builder.config.templates.changes.clone(),
+            vec![
+                ("title", HtmlText::new(format!("Changes since {}", self.old_version)).into()),
+                ("total", HtmlText::new(self.summary()).into()),
+                ("added", Self::fmt_items(&self.added, true)),
+                ("removed", Self::fmt_items(&self.removed, false)),
+                ("changed", self.fmt_changed()),
+            ],
+        )
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!(
+            "Changes to the API of {} since {}",
+            builder.config.project.name, self.old_version
+        )
+    }
+}
+
+/// Compare the APIs of two builds for `flash diff` and print the result
+pub fn diff_builds(old: &Path, new: &Path, format: OutputFormat) -> Result<(), String> {
+    let diff = ApiDiff::new(&ApiModel::load(old)?, &ApiModel::load(new)?);
+    match format {
+        OutputFormat::Text => diff.print_text(),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&diff)
+                .map_err(|e| format!("Unable to serialize changes: {e}"))?
+        ),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fun(ret: &str, params: &[&str]) -> ApiEntity {
+        ApiEntity {
+            kind: "member function".into(),
+            ty: Some(ret.into()),
+            params: Some(params.iter().map(|p| p.to_string()).collect()),
+            bases: Vec::new(),
+            is_static: false,
+            is_virtual: false,
+            is_pure_virtual: false,
+            is_const: false,
+            is_deprecated: false,
+            url: None,
+        }
+    }
+
+    fn model(version: &str, entities: Vec<(&str, Vec<ApiEntity>)>) -> ApiModel {
+        ApiModel {
+            project: "Geode".into(),
+            version: version.into(),
+            entities: entities.into_iter().map(|(name, list)| (name.to_owned(), list)).collect(),
+        }
+    }
+
+    fn signatures(items: &[DiffItem]) -> Vec<&str> {
+        items.iter().map(|i| i.signature.as_str()).collect()
+    }
+
+    #[test]
+    fn signature_format() {
+        let mut get = fun("int", &["float"]);
+        get.is_virtual = true;
+        get.is_const = true;
+        assert_eq!(get.signature("Mod::get"), "virtual int Mod::get(float) const");
+    }
+
+    #[test]
+    fn same_api_has_no_changes() {
+        let old = model("1.0", vec![("Mod::get", vec![fun("int", &[])])]);
+        let new = model("1.1", vec![("Mod::get", vec![fun("int", &[])])]);
+        assert!(ApiDiff::new(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn added_and_removed() {
+        let old = model("1.0", vec![("Mod::old", vec![fun("void", &[])])]);
+        let new = model("2.0", vec![("Mod::new", vec![fun("void", &[])])]);
+        let diff = ApiDiff::new(&old, &new);
+        assert_eq!(signatures(&diff.added), vec!["void Mod::new()"]);
+        assert_eq!(signatures(&diff.removed), vec!["void Mod::old()"]);
+        assert!(diff.changed.is_empty());
+    }
+
+    #[test]
+    fn changed_overload() {
+        let old = model("1.0", vec![(
+            "Mod::get",
+            vec![fun("int", &["int"]), fun("int", &["float"])],
+        )]);
+        let new = model("2.0", vec![(
+            "Mod::get",
+            vec![fun("int", &["int"]), fun("int", &["double"])],
+        )]);
+        let diff = ApiDiff::new(&old, &new);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].old, "int Mod::get(float)");
+        assert_eq!(diff.changed[0].changes, vec!["Parameter 1 changed from `float` to `double`"]);
+    }
+
+    #[test]
+    fn new_overload_is_added() {
+        let old = model("1.0", vec![("Mod::get", vec![fun("int", &["int"])])]);
+        let new = model("2.0", vec![(
+            "Mod::get",
+            vec![fun("int", &["int"]), fun("int", &["float"])],
+        )]);
+        let diff = ApiDiff::new(&old, &new);
+        assert_eq!(signatures(&diff.added), vec!["int Mod::get(float)"]);
+        assert!(diff.removed.is_empty() && diff.changed.is_empty());
+    }
+
+    #[test]
+    fn const_overload_is_added() {
+        let mut get_const = fun("int", &[]);
+        get_const.is_const = true;
+        let old = model("1.0", vec![("Mod::get", vec![fun("int", &[])])]);
+        let new = model("2.0", vec![("Mod::get", vec![get_const, fun("int", &[])])]);
+        let diff = ApiDiff::new(&old, &new);
+        assert_eq!(signatures(&diff.added), vec!["int Mod::get() const"]);
+        assert!(diff.removed.is_empty() && diff.changed.is_empty());
+    }
+
+    #[test]
+    fn changed_qualifiers() {
+        let mut get = fun("int", &[]);
+        get.is_const = true;
+        get.is_deprecated = true;
+        let old = model("1.0", vec![("Mod::get", vec![fun("int", &[])])]);
+        let new = model("2.0", vec![("Mod::get", vec![get])]);
+        let diff = ApiDiff::new(&old, &new);
+        assert_eq!(diff.changed[0].changes, vec!["Is now const", "Is now deprecated"]);
+    }
+}
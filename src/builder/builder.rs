@@ -6,11 +6,11 @@ use tokio::task::JoinHandle;
 
 use crate::{
     config::Config,
-    html::{GenHtml, Html, process::{minify_js, minify_css, minify_html}},
+    html::{GenHtml, Html, icons::inline_icons, links::relative_links, offline::{offline_head, write_offline_assets}, process::{minify_js, minify_css, minify_html}},
     url::UrlPath,
 };
 
-use super::{files::Root, namespace::Namespace, tutorial::TutorialFolder, traits::{OutputEntry, BuildResult, Entry}};
+use super::{api::{ApiDiff, ApiModel}, coverage::Coverage, deprecated::DeprecatedList, files::Root, group::GroupList, index::SymbolIndex, namespace::Namespace, shared::collect_autolinks, tutorial::TutorialFolder, traits::{OutputEntry, BuildResult, Entry}};
 
 pub struct Builder<'e> {
     pub config: Arc<Config>,
@@ -19,7 +19,15 @@ pub struct Builder<'e> {
     pub index: &'e clang::Index<'e>,
     pub args: &'e [String],
     file_roots: Vec<Root>,
-    tutorials: TutorialFolder,
+    pub tutorials: TutorialFolder,
+    /// Entity names that get autolinked in descriptions, mapped to their URLs
+    pub autolinks: HashMap<String, String>,
+    symbol_index: SymbolIndex,
+    groups: GroupList<'e>,
+    coverage: Option<Coverage>,
+    deprecated: DeprecatedList<'e>,
+    api: Option<ApiModel>,
+    changes: Option<ApiDiff>,
     nav_cache: Option<String>,
 }
 
@@ -39,6 +47,13 @@ impl<'e> Builder<'e> {
             args,
             file_roots: Root::from_config(config.clone()),
             tutorials: TutorialFolder::from_config(config),
+            autolinks: HashMap::new(),
+            symbol_index: SymbolIndex,
+            groups: GroupList::default(),
+            coverage: None,
+            deprecated: DeprecatedList::default(),
+            api: None,
+            changes: None,
             nav_cache: None,
         }
         .setup()
@@ -61,6 +76,10 @@ impl<'e> Builder<'e> {
             ).map_err(|e| format!("Unable to copy {}: {e}", script.name))?;
         }
 
+        if self.config.cli.offline {
+            write_offline_assets(&self.config.output_dir)?;
+        }
+
         // copy icon
         if let Some(ref icon) = self.config.project.icon {
             fs::copy(
@@ -106,6 +125,27 @@ impl<'e> Builder<'e> {
             }
         }
 
+        // index names for autolinking so descriptions don't have to search 
+        // through the whole tree for every word
+        for entry in self.root.entries.values() {
+            collect_autolinks(entry, self.config.clone(), &mut self.autolinks);
+        }
+
+        // find groups before building the nav so they show up there
+        self.groups = GroupList::from_builder(&self);
+
+        self.deprecated = DeprecatedList::from_builder(&self);
+
+        if self.config.cli.coverage {
+            self.coverage = Some(Coverage::from_builder(&self));
+        }
+
+        let api = ApiModel::from_builder(&self);
+        if let Some(ref old) = self.config.previous_api {
+            self.changes = Some(ApiDiff::new(old, &api));
+        }
+        self.api = Some(api);
+
         // prebuild nav for performance
         self.prebuild()?;
 
@@ -141,7 +181,7 @@ impl<'e> Builder<'e> {
                 format!("{} - {} Docs", name, config.project.name)
             };
 
-            let mut fmt = default_format(config.clone());
+            let mut fmt = page_format(config.clone(), &target_url);
             fmt.extend(HashMap::from([
                 (
                     "page_url".to_owned(),
@@ -156,12 +196,17 @@ impl<'e> Builder<'e> {
                     .collect::<Vec<_>>(),
             );
 
-            let content = minify_html(
-                strfmt(&template, &fmt)
-                .map_err(|e| format!("Unable to format {target_url}: {e}"))?
-            )?;
+            let mut content = strfmt(&template, &fmt)
+                .map_err(|e| format!("Unable to format {target_url}: {e}"))?;
+            if config.cli.offline {
+                content = inline_icons(&content, &config.diagnostics);
+            }
+            if config.cli.relative_links {
+                content = relative_links(&content, &target_url, &config);
+            }
+            let content = minify_html(content)?;
 
-            let mut page_fmt = default_format(config.clone());
+            let mut page_fmt = page_format(config.clone(), &target_url);
             page_fmt.extend(HashMap::from([
                 (
                     "head_content".to_owned(),
@@ -171,10 +216,15 @@ impl<'e> Builder<'e> {
                 ("navbar_content".to_owned(), nav),
                 ("main_content".to_owned(), content.clone()),
             ]));
-            let page = minify_html(
-                strfmt(&config.templates.page, &page_fmt)
-                .map_err(|e| format!("Unable to format {target_url}: {e}"))?
-            )?;
+            let mut page = strfmt(&config.templates.page, &page_fmt)
+                .map_err(|e| format!("Unable to format {target_url}: {e}"))?;
+            if config.cli.offline {
+                page = inline_icons(&page, &config.diagnostics);
+            }
+            if config.cli.relative_links {
+                page = relative_links(&page, &target_url, &config);
+            }
+            let page = minify_html(page)?;
             
             let output_dir = config.output_dir.join(target_url.to_pathbuf());
 
@@ -222,6 +272,11 @@ impl<'e> Builder<'e> {
             .map(|p| p.1 as &dyn Entry<'e>)
             .chain(self.file_roots.iter().map(|p| p as &dyn Entry<'e>))
             .chain([&self.tutorials as &dyn Entry])
+            .chain([&self.symbol_index as &dyn Entry])
+            .chain([&self.groups as &dyn Entry])
+            .chain(self.coverage.iter().map(|c| c as &dyn Entry<'e>))
+            .chain([&self.deprecated as &dyn Entry])
+            .chain(self.changes.iter().map(|c| c as &dyn Entry<'e>))
             .collect()
     }
 
@@ -263,16 +318,46 @@ impl<'e> Builder<'e> {
             pbar.set_message("Generating metadata".to_string());
         }
 
-        fs::write(
-            self.config.output_dir.join("functions.json"),
-            serde_json::to_string(
-                &self.root.nav().suboptions_titles(self.config.clone())
-                    .into_iter()
-                    .map(|(n, c)| if c > 0 { format!("{} ({})", n, c + 1) } else { n })
-                    .collect::<Vec<_>>()
-            ).map_err(|e| format!("Unable to save metadata {e}"))?
+        let functions = serde_json::to_string(
+            &self.root.nav().suboptions_titles(self.config.clone())
+                .into_iter()
+                .map(|(n, c)| if c > 0 { format!("{} ({})", n, c + 1) } else { n })
+                .collect::<Vec<_>>()
         ).map_err(|e| format!("Unable to save metadata {e}"))?;
 
+        // Browsers don't allow fetching files from disk, but scripts can
+        // still be loaded
+        if self.config.cli.relative_links {
+            fs::write(
+                self.config.output_dir.join("functions.js"),
+                format!("const FLASH_FUNCTIONS = {functions};"),
+            ).map_err(|e| format!("Unable to save metadata {e}"))?;
+        }
+        fs::write(self.config.output_dir.join("functions.json"), functions)
+            .map_err(|e| format!("Unable to save metadata {e}"))?;
+
+        // Save the API so later builds can be compared against this one
+        if let Some(ref api) = self.api {
+            api.save(&self.config.output_dir)?;
+        }
+
+        if let Some(ref coverage) = self.coverage {
+            self.config.diagnostics.set_coverage(coverage.percent());
+        }
+        if let Some(ref coverage) = self.coverage
+            && let Some(min) = self.config.cli.min_coverage
+            && coverage.percent() < min
+        {
+            self.config.diagnostics.error(
+                "min-coverage",
+                None,
+                format!(
+                    "Documentation coverage {:.1}% is below the required {min}%",
+                    coverage.percent()
+                ),
+            );
+        }
+
         Ok(())
     }
 
@@ -286,6 +371,21 @@ impl<'e> Builder<'e> {
                 "tutorial_content".into(),
                 self.tutorials.nav().to_html(self.config.clone()).gen_html(),
             ),
+            (
+                "group_content".into(),
+                self.groups.nav().to_html(self.config.clone()).gen_html(),
+            ),
+            (
+                "page_links".into(),
+                (!self.deprecated.is_empty())
+                    .then(|| self.deprecated.nav())
+                    .into_iter()
+                    .chain(self.coverage.iter().map(|c| c.nav()))
+                    .chain(self.changes.iter().map(|c| c.nav()))
+                    .map(|nav| nav.to_html(self.config.clone()).gen_html())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
             (
                 "entity_content".into(),
                 self.root.nav().to_html(self.config.clone()).gen_html(),
@@ -311,6 +411,14 @@ impl<'e> Builder<'e> {
 
 fn default_format(config: Arc<Config>) -> HashMap<String, String> {
     HashMap::from([
+        (
+            "external_assets".into(),
+            if config.cli.offline {
+                offline_head(&config)
+            } else {
+                include_str!("../../templates/cdn.html").to_string()
+            },
+        ),
         ("project_name".into(), config.project.name.clone()),
         ("project_version".into(), config.project.version.clone()),
         (
@@ -340,5 +448,50 @@ fn default_format(config: Arc<Config>) -> HashMap<String, String> {
                 .unwrap_or(&UrlPath::new())
                 .to_string(),
         ),
+        (
+            "root_url".into(),
+            config
+                .output_url
+                .as_ref()
+                .unwrap_or(&UrlPath::new())
+                .to_string(),
+        ),
+        ("relative_links".into(), config.cli.relative_links.to_string()),
+        (
+            "version_switcher".into(),
+            // Browsers don't allow fetching versions.json from disk, and the
+            // other versions' URLs are absolute, so relative docs don't get
+            // a switcher
+            config
+                .version_dir
+                .as_ref()
+                .filter(|_| !config.cli.relative_links)
+                .map(|dir| {
+                    format!(
+                        "<div class=\"versions\"><select id=\"nav-version\" data-versions=\"{}/versions.json\" \
+                        data-current=\"{dir}\"><option value=\"{}\">{dir}</option></select></div>",
+                        config.root_url.as_ref().unwrap_or(&UrlPath::new()),
+                        config.output_url.as_ref().unwrap_or(&UrlPath::new()),
+                    )
+                })
+                .unwrap_or_default(),
+        ),
     ])
 }
+
+/// Format args for a page at `url`. With relative links, scripts on the page
+/// find the docs root relative to the page too
+fn page_format(config: Arc<Config>, url: &UrlPath) -> HashMap<String, String> {
+    let mut fmt = default_format(config.clone());
+    fmt.insert(
+        "canonical_link".into(),
+        config
+            .canonical_url(&url.to_absolute(config.clone()))
+            .map(|url| format!("<link rel=\"canonical\" href=\"{url}\">"))
+            .unwrap_or_default(),
+    );
+    if config.cli.relative_links {
+        fmt.insert("root_url".into(), UrlPath::new().relative_to(url));
+    }
+    fmt
+}
@@ -38,7 +38,7 @@ impl<'e> Entry<'e> for Class<'e> {
         NavItem::new_link(
             &self.name(), self.url(), Some(("box", false)),
             SubItem::for_classlike(&self.entity)
-        )
+        ).deprecated(self.entity.is_deprecated())
     }
 }
 
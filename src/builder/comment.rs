@@ -1,25 +1,117 @@
-use std::{collections::HashMap, fs, str::Chars};
+use std::{collections::{HashMap, HashSet}, fs, str::Chars};
 
 use clang::{
     token::{Token, TokenKind},
-    Entity, EntityKind,
+    Availability, Entity, EntityKind, TypeKind,
 };
 use multipeek::{IteratorExt, MultiPeek};
 
 use crate::{
+    config::{CommandDef, CommandKind},
     html::{Html, HtmlElement, HtmlList, HtmlText},
-    url::UrlPath,
 };
 
 use super::{
     builder::Builder,
+    doctest::write_temp_source,
+    highlight::highlight,
+    snippet::read_snippet,
     traits::EntityMethods,
     shared::fmt_autolinks,
     markdown::fmt_markdown,
+    resolve::{fmt_doc_links, link_code_spans, resolve, Target},
 };
 
+/// Stand-ins for `@` and `\\` that must not start a command, like escaped
+/// ones and ones in code. They are swapped back once values are parsed
+const LITERAL_AT: char = '\u{E000}';
+const LITERAL_BACKSLASH: char = '\u{E001}';
+
+fn literal(code: &str) -> String {
+    code.replace('@', &LITERAL_AT.to_string())
+        .replace('\\', &LITERAL_BACKSLASH.to_string())
+}
+
+fn restore_literals(text: &str) -> String {
+    text.replace(LITERAL_AT, "@").replace(LITERAL_BACKSLASH, "\\")
+}
+
+/// Position of the first `\name` or `@name` command in `text`
+fn find_command(text: &str, name: &str) -> Option<usize> {
+    text.match_indices(['\\', '@']).map(|(i, _)| i).find(|&i| {
+        text[i + 1..].strip_prefix(name).is_some_and(|after| !after.starts_with(char::is_alphanumeric))
+    })
+}
+
+/// Commands Flash knows, including Doxygen ones it ignores. A backslash
+/// followed by anything else, like in `\n`, is plain text
+const COMMANDS: &[&str] = &[
+    "description", "desc", "brief", "details", "param", "arg", "tparam", "targ", "return",
+    "returns", "result", "retval", "throws", "throw", "exception", "see", "sa", "note",
+    "remark", "remarks", "warning", "warn", "attention", "version", "since", "example", "code",
+    "endcode", "ingroup", "defgroup", "deprecated", "pre", "post", "invariant", "threadsafety",
+    "threadsafe", "inheritdoc", "copydoc", "c", "p", "a", "e", "em", "b",
+    // Doxygen commands without an equivalent
+    "file", "class", "struct", "fn", "namespace", "def", "internal", "overload", "todo", "bug",
+    "author", "authors", "date", "copyright", "addtogroup", "name",
+];
+
+/// The name of the command at the start of `text`, which is right after its
+/// `@` or `\\`
+fn command_name(text: &str) -> &str {
+    &text[..text.find(|c: char| !c.is_alphabetic()).unwrap_or(text.len())]
+}
+
+fn is_command(name: &str, custom: &HashMap<String, CommandDef>) -> bool {
+    COMMANDS.contains(&name) || custom.contains_key(name)
+}
+
+/// Check if the `@` or `\\` at the start of `text` is plain text rather than
+/// a command, because it's in the middle of a word or no command name follows
+/// it. Unknown `@` commands are still commands, but unknown backslash ones are
+/// usually escapes like `\\n`
+fn is_text(text: &str, prev: Option<char>, custom: &HashMap<String, CommandDef>) -> bool {
+    let name = command_name(&text[1..]);
+    prev.is_some_and(char::is_alphanumeric)
+        || name.is_empty()
+        || (text.starts_with('\\') && !is_command(name, custom))
+}
+
+/// The `\code`, `@code` or `@example` command at the start of `text` and its
+/// length with attributes, if the code follows it in the comment instead of
+/// being read from a file
+fn code_command(text: &str) -> Option<(&'static str, usize)> {
+    let after = text.strip_prefix(['\\', '@'])?;
+    let cmd = ["code", "example"].into_iter().find(|cmd| command_name(after) == *cmd)?;
+    let after = &after[cmd.len()..];
+    let attrs = if after.starts_with('[') {
+        after.find(']').map_or(0, |end| end + 1)
+    }
+    else {
+        0
+    };
+    (!after[..attrs].contains("file")).then_some((cmd, 1 + cmd.len() + attrs))
+}
+
+/// Check if a doc comment has a command like `@defgroup` the same way
+/// `JSDocComment` would find it, without parsing the whole comment
+pub fn has_command(raw: &str, name: &str) -> bool {
+    let raw = CommentLexer::normalize(raw, &HashMap::new());
+    let mut lexer = CommentLexer::new(&raw);
+    while let Some(cmd) = lexer.next_command() {
+        if cmd.cmd == name {
+            return true;
+        }
+        lexer.next_value();
+    }
+    false
+}
+
 struct CommentLexer<'s> {
     raw: MultiPeek<Chars<'s>>,
+    /// Problems found while parsing as diagnostic codes and messages,
+    /// reported by the comment once it knows where it is
+    warnings: Vec<(&'static str, String)>,
 }
 
 impl<'s> CommentLexer<'s> {
@@ -28,9 +120,159 @@ impl<'s> CommentLexer<'s> {
             raw: raw
                 .trim_end_matches("*/")
                 .trim_start_matches("/*")
+                // Doxygen's /*! and /**< comments
+                .trim_start_matches(['*', '!', '<'])
                 .chars()
                 .multipeek(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Convert Doxygen's `///` and `//!` line comments into a plain comment
+    /// body and replace inline formatting commands like `\c word` with the
+    /// equivalent markdown
+    pub fn normalize(raw: &str, custom: &HashMap<String, CommandDef>) -> String {
+        let body = if raw.trim_start().starts_with("//") {
+            raw.lines()
+                .map(|line| {
+                    let line = line.trim_start();
+                    line.strip_prefix("///")
+                        .or_else(|| line.strip_prefix("//!"))
+                        .or_else(|| line.strip_prefix("//"))
+                        .unwrap_or(line)
+                })
+                .map(|line| line.strip_prefix('<').unwrap_or(line))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        else {
+            raw.to_owned()
+        };
+
+        let body = Self::protect_literals(&body, custom);
+        let mut res = String::with_capacity(body.len());
+        let mut rest = body.as_str();
+        while let Some(pos) = rest.find(['\\', '@']) {
+            res.push_str(&rest[..pos]);
+            let after = &rest[pos + 1..];
+            let cmd_len = after
+                .find(|c: char| !c.is_alphabetic())
+                .unwrap_or(after.len());
+            let fmt = match &after[..cmd_len] {
+                "c" | "p" => Some("`"),
+                "a" | "e" | "em" => Some("*"),
+                "b" => Some("**"),
+                _ => None,
+            };
+            if let Some(fmt) = fmt && after[cmd_len..].starts_with([' ', '\t']) {
+                let word = after[cmd_len..].trim_start_matches([' ', '\t']);
+                let word = &word[..word.find(char::is_whitespace).unwrap_or(word.len())];
+                // Trailing punctuation isn't part of the formatted word
+                let word = word.trim_end_matches(['.', ',', ';', ':', ')']);
+                res.push_str(&format!("{fmt}{word}{fmt}"));
+                rest = &after[cmd_len..].trim_start_matches([' ', '\t'])[word.len()..];
+            }
+            else {
+                res.push_str(&rest[pos..pos + 1]);
+                rest = after;
+            }
+        }
+        res.push_str(rest);
+        res
+    }
+
+    /// Replace `\\@` and `@@` escapes, and any `@` or `\\` in code spans,
+    /// fenced code blocks, `\\code` blocks and examples, with stand-ins so
+    /// they aren't parsed as commands. So are ones in the middle of a word and
+    /// backslashes that aren't followed by a command, like in `\\n`
+    fn protect_literals(body: &str, custom: &HashMap<String, CommandDef>) -> String {
+        let mut res = String::with_capacity(body.len());
+        let mut fence = None;
+        let mut code_block = None;
+        for line in body.split_inclusive('\n') {
+            let content = line.trim_start();
+            let content = content.strip_prefix('*').unwrap_or(content).trim_start();
+
+            // Examples go on until the next line that starts with a command
+            if code_block == Some("example")
+                && let Some(after) = content.strip_prefix(['@', '\\'])
+                && is_command(command_name(after), custom)
+            {
+                code_block = None;
+            }
+
+            if let Some(f) = fence {
+                if content.starts_with(f) {
+                    fence = None;
+                }
+                res.push_str(&literal(line));
+                continue;
+            }
+            if code_block.is_none() && let Some(f) = ["```", "~~~"].into_iter().find(|f| content.starts_with(f)) {
+                fence = Some(f);
+                res.push_str(&literal(line));
+                continue;
+            }
+
+            let mut rest = line;
+            loop {
+                // Everything up to `\endcode` is code, which may contain
+                // what look like formatting commands
+                if code_block.is_some() {
+                    let end = match code_block {
+                        Some("code") => find_command(rest, "endcode"),
+                        _ => None,
+                    };
+                    let Some(end) = end else {
+                        res.push_str(&literal(rest));
+                        rest = "";
+                        break;
+                    };
+                    res.push_str(&literal(&rest[..end]));
+                    rest = &rest[end..];
+                    code_block = None;
+                }
+                let Some(pos) = rest.find(['`', '@', '\\']) else {
+                    break;
+                };
+                res.push_str(&rest[..pos]);
+                let tail = &rest[pos..];
+                if tail.starts_with("\\@") || tail.starts_with("@@") {
+                    res.push(LITERAL_AT);
+                    rest = &tail[2..];
+                }
+                else if !tail.starts_with('`') && is_text(tail, res.chars().next_back(), custom) {
+                    res.push_str(&literal(&tail[..1]));
+                    rest = &tail[1..];
+                }
+                else if let Some((cmd, len)) = code_command(tail) {
+                    res.push_str(&tail[..len]);
+                    rest = &tail[len..];
+                    code_block = Some(cmd);
+                }
+                else if tail.starts_with('`') {
+                    // Code spans have to be closed on the same line
+                    let ticks = tail.len() - tail.trim_start_matches('`').len();
+                    match tail[ticks..].find(&tail[..ticks]) {
+                        Some(end) => {
+                            let len = ticks + end + ticks;
+                            res.push_str(&literal(&tail[..len]));
+                            rest = &tail[len..];
+                        }
+                        None => {
+                            res.push_str(&tail[..ticks]);
+                            rest = &tail[ticks..];
+                        }
+                    }
+                }
+                else {
+                    res.push_str(&tail[..1]);
+                    rest = &tail[1..];
+                }
+            }
+            res.push_str(rest);
         }
+        res
     }
 
     fn skip_while<P: FnMut(char) -> bool>(&mut self, mut pred: P) -> usize {
@@ -97,7 +339,7 @@ impl<'s> CommentLexer<'s> {
                 res.push(c);
             }
         }
-        res = res.trim().to_owned();
+        res = restore_literals(res.trim());
         // println!("indent_size: {:?}", indent_size);
         (!res.is_empty()).then_some(res)
     }
@@ -110,14 +352,15 @@ impl<'s> CommentLexer<'s> {
         // Skip whitespace
         self.skip_to_next_value();
 
-        // todo: handle escaped @ symbol
-
         match self.raw.peek()? {
-            '@' => {
+            '@' | '\\' => {
                 // Consume param symbol
                 self.raw.next();
-                // Eat command name
-                let cmd = self.eat_until(|c| c.is_whitespace() || c == '[')?;
+                // Eat command name. A lone `@` is part of the text rather
+                // than the end of the comment
+                let Some(cmd) = self.eat_until(|c| c.is_whitespace() || c == '[') else {
+                    return Some(ParsedCommand::new("description"));
+                };
                 // Parse attributes if those are provided
                 if self.raw.peek().is_some_and(|c| *c == '[') {
                     // Consume opening bracket
@@ -171,26 +414,47 @@ impl<'s> CommentLexer<'s> {
         self.eat_word()
     }
 
+    /// Check if `c` starts the next command. Inline commands like
+    /// `{@link}` are part of the value instead, an `@` in the middle of a word
+    /// like an email address isn't a command, and Doxygen-style backslash
+    /// commands only count at the start of a word so paths and escapes in
+    /// text don't get mistaken for them
+    fn is_command_start(c: char, prev: &mut Option<char>) -> bool {
+        let res = match c {
+            '@' => prev.is_none_or(|p| !p.is_alphanumeric() && p != '{'),
+            '\\' => prev.is_none_or(|p| p.is_whitespace()),
+            _ => false,
+        };
+        *prev = Some(c);
+        res
+    }
+
     pub fn next_value(&mut self) -> Option<String> {
-        self.eat_until(|c| c == '@')
+        let mut prev = None;
+        self.eat_until(|c| Self::is_command_start(c, &mut prev))
+    }
+
+    pub fn next_line(&mut self) -> Option<String> {
+        let mut prev = None;
+        self.eat_until(|c| c == '\n' || Self::is_command_start(c, &mut prev))
     }
 
     pub fn param_for(&mut self, cmd: &ParsedCommand) -> String {
         self.next_param().unwrap_or_else(|| {
-            println!(
-                "Warning parsing JSDoc comment: Expected parameter for command {}",
-                cmd.cmd
-            );
+            self.warnings.push((
+                "comment-syntax",
+                format!("Expected parameter for command @{}", cmd.cmd),
+            ));
             String::new()
         })
     }
 
     pub fn value_for(&mut self, cmd: &ParsedCommand) -> String {
         self.next_value().unwrap_or_else(|| {
-            println!(
-                "Warning parsing JSDoc comment: Expected value for command {}",
-                cmd.cmd
-            );
+            self.warnings.push((
+                "comment-syntax",
+                format!("Expected value for command @{}", cmd.cmd),
+            ));
             String::new()
         })
     }
@@ -223,29 +487,80 @@ impl ParsedCommand {
 
 struct Annotation {
     location: u32,
-    link: UrlPath,
-    class: String,
+    link: Option<String>,
+    class: &'static str,
 }
 
 impl Annotation {
-    pub fn from(value: &Entity, builder: &Builder, class: String) -> Option<Annotation> {
+    /// Annotate the token at the location of `at`, linking to the docs of 
+    /// `target` if it has any
+    pub fn new(at: &Entity, target: Option<&Entity>, builder: &Builder, class: &'static str) -> Option<Annotation> {
         Some(Self {
-            location: value.get_range()?.get_start().get_file_location().offset,
-            link: value
-                .abs_docs_url(builder.config.clone())?
-                .to_absolute(builder.config.clone()),
+            location: at.get_location()?.get_file_location().offset,
+            link: target.and_then(|t| docs_link(t, builder)),
             class,
         })
     }
+}
 
-    pub fn from_end(value: &Entity, builder: &Builder, class: String) -> Option<Annotation> {
-        Some(Self {
-            location: value.get_range()?.get_end().get_file_location().offset - 1,
-            link: value
-                .abs_docs_url(builder.config.clone())?
-                .to_absolute(builder.config.clone()),
-            class,
-        })
+/// Link to the docs of something referenced in an example. Member functions
+/// link to their place on the class page, and fields and enum constants to
+/// the page of the class or enum they are in
+fn docs_link(entity: &Entity, builder: &Builder) -> Option<String> {
+    // Things declared in the example itself have no docs
+    if entity.is_in_main_file() && entity.get_kind() != EntityKind::InclusionDirective {
+        return None;
+    }
+    let target = match entity.get_kind() {
+        EntityKind::Method
+        | EntityKind::Constructor
+        | EntityKind::Destructor
+        | EntityKind::ConversionFunction => Target::Member(entity.get_semantic_parent()?, *entity),
+        EntityKind::FieldDecl | EntityKind::EnumConstantDecl => {
+            Target::Entity(entity.get_semantic_parent()?)
+        }
+        _ => Target::Entity(*entity),
+    };
+    target.href(builder.config.clone())
+}
+
+/// Class of a reference to `entity` in an example
+fn reference_class(entity: &Entity) -> &'static str {
+    match entity.get_kind() {
+        EntityKind::FunctionDecl
+        | EntityKind::FunctionTemplate
+        | EntityKind::Method
+        | EntityKind::Constructor
+        | EntityKind::Destructor
+        | EntityKind::ConversionFunction => "function",
+        EntityKind::FieldDecl => "property",
+        EntityKind::ParmDecl => "parameter",
+        EntityKind::EnumConstantDecl => "enum-constant",
+        EntityKind::Namespace | EntityKind::NamespaceAlias => "namespace",
+        EntityKind::VarDecl => "variable",
+        _ => "class-name",
+    }
+}
+
+/// Class of a token that hasn't been annotated
+fn token_class(token: &Token) -> &'static str {
+    match token.get_kind() {
+        TokenKind::Comment => "comment",
+        TokenKind::Identifier => "identifier",
+        TokenKind::Keyword => match token.get_spelling().as_str() {
+            "true" | "false" | "nullptr" => "boolean",
+            _ => "keyword",
+        },
+        TokenKind::Literal => {
+            let spelling = token.get_spelling();
+            if spelling.contains(['"', '\'']) {
+                "string"
+            }
+            else {
+                "number"
+            }
+        }
+        TokenKind::Punctuation => "punctuation",
     }
 }
 
@@ -279,39 +594,29 @@ fn annotate(base: Entity, annotations: &[Annotation]) -> Vec<Html> {
             );
         }
 
-        let classes: &[&str] = match token.get_kind() {
-            TokenKind::Comment => &["comment"],
-            TokenKind::Identifier => &["identifier"],
-            TokenKind::Keyword => match token.get_spelling().as_str() {
-                "true" | "false" | "this" => &["keyword", "value"],
-                _ => &["keyword"],
-            },
-            TokenKind::Literal => &["literal"],
-            TokenKind::Punctuation => &["punctuation"],
-        };
-
         // Add link
-        if let Some(a) = annotations
+        let annotation = annotations
             .iter()
-            .find(|a| token_start.offset <= a.location && a.location <= token_end.offset)
-        {
-            list.push(
+            .find(|a| token_start.offset <= a.location && a.location < token_end.offset);
+        let class = annotation
+            .filter(|_| token.get_kind() == TokenKind::Identifier)
+            .map(|a| a.class)
+            .unwrap_or(token_class(&token));
+        match annotation.and_then(|a| a.link.as_ref()) {
+            Some(link) => list.push(
                 HtmlElement::new("a")
-                    .with_classes(classes)
-                    .with_class(&a.class)
-                    .with_attr("href", a.link.clone())
+                    .with_class(class)
+                    .with_attr("href", link)
                     .with_text(token.get_spelling())
                     .into(),
-            );
-        }
-        // Add just the colorized token
-        else {
-            list.push(
+            ),
+            // Add just the colorized token
+            None => list.push(
                 HtmlElement::new("span")
-                    .with_classes(classes)
+                    .with_class(class)
                     .with_text(token.get_spelling())
                     .into(),
-            );
+            ),
         }
 
         // Save current token as the previous in loop
@@ -321,40 +626,6 @@ fn annotate(base: Entity, annotations: &[Annotation]) -> Vec<Html> {
     list
 }
 
-fn print(entity: &Entity) {
-    for child in entity.get_children() {
-        println!(
-            "{:?} :: {}:{}..{}:{} => {:?}",
-            entity.get_kind(),
-            child
-                .get_range()
-                .unwrap()
-                .get_start()
-                .get_file_location()
-                .line,
-            child
-                .get_range()
-                .unwrap()
-                .get_start()
-                .get_file_location()
-                .column,
-            child
-                .get_range()
-                .unwrap()
-                .get_end()
-                .get_file_location()
-                .line,
-            child
-                .get_range()
-                .unwrap()
-                .get_end()
-                .get_file_location()
-                .column,
-            child.get_kind(),
-        );
-    }
-}
-
 pub struct Example<'e> {
     builder: &'e Builder<'e>,
     data: String,
@@ -371,46 +642,46 @@ impl<'e> Example<'e> {
     }
 
     fn get_annotations(&self, entity: Entity<'e>) -> Vec<Annotation> {
-        if !entity.is_in_main_file() {
+        if !entity.is_in_main_file() && entity.get_kind() != EntityKind::TranslationUnit {
             return Vec::new();
         }
 
         let mut res = Vec::new();
+        let target = entity.get_reference();
 
-        if entity.get_kind() != EntityKind::TranslationUnit {
-            print(&entity);
-        }
+        let annotation = match entity.get_kind() {
+            // References to types, templates and namespaces
+            EntityKind::TypeRef | EntityKind::TemplateRef => {
+                Annotation::new(&entity, target.as_ref(), self.builder, "class-name")
+            }
+            EntityKind::NamespaceRef => {
+                Annotation::new(&entity, target.as_ref(), self.builder, "namespace")
+            }
 
-        match entity.get_kind() {
-            // Types
-            EntityKind::TypeRef |
-            // Templated types
-            EntityKind::TemplateRef => {
-                if let Some(p) = Annotation::from(&entity, self.builder, "class".into()) {
-                    res.push(p);
-                }
-            },
+            // References to functions, variables, members and enum constants
+            EntityKind::DeclRefExpr
+            | EntityKind::MemberRefExpr
+            | EntityKind::MemberRef
+            | EntityKind::OverloadedDeclRef => target.as_ref().and_then(|t| {
+                Annotation::new(&entity, Some(t), self.builder, reference_class(t))
+            }),
+
+            // Things declared in the example itself
+            EntityKind::VarDecl | EntityKind::ParmDecl | EntityKind::FieldDecl
+            | EntityKind::FunctionDecl | EntityKind::Method => {
+                Annotation::new(&entity, None, self.builder, reference_class(&entity))
+            }
 
             EntityKind::InclusionDirective => {
-                if let Some(p) = Annotation::from(&entity, self.builder, "macro".into()) {
-                    res.push(p);
-                }
-            },
-
+                Annotation::new(&entity, Some(&entity), self.builder, "macro")
+            }
             EntityKind::MacroExpansion => {
-                if let Some(p) = Annotation::from(&entity, self.builder, "macro".into()) {
-                    res.push(p);
-                }
-            },
-
-            EntityKind::CallExpr => {
-                if let Some(p) = Annotation::from_end(&entity.get_child(0).unwrap(), self.builder, "function".into()) {
-                    res.push(p);
-                }
-            },
+                Annotation::new(&entity, target.as_ref(), self.builder, "macro")
+            }
 
-            _ => {},
-        }
+            _ => None,
+        };
+        res.extend(annotation);
 
         for child in entity.get_children() {
             res.extend(self.get_annotations(child));
@@ -419,21 +690,24 @@ impl<'e> Example<'e> {
         res
     }
 
+    /// Create an example from the code in a `// [region]` of a file in the
+    /// project
+    pub fn from_file(
+        path: &str,
+        region: Option<&str>,
+        analyze: bool,
+        builder: &'e Builder<'e>,
+    ) -> Result<Self, String> {
+        Ok(Self::new(read_snippet(&builder.config, path, region)?, analyze, builder))
+    }
+
+    pub fn code(&self) -> &str {
+        &self.data
+    }
+
     fn try_to_analyzed_html(&self) -> Result<Html, String> {
         // Create a temporary file to store the example's code in
-        let mut num = 0;
-        let path = loop {
-            let path = self
-                .builder
-                .config
-                .output_dir
-                .join(format!("_example_{num}.cpp"));
-            if !path.exists() {
-                break path;
-            }
-            num += 1;
-        };
-        fs::write(&path, &self.data).map_err(|e| e.to_string())?;
+        let path = write_temp_source(self.builder, &self.data)?;
 
         // Parse this file using builder's index to avoid reparsing everything
         let unit = self
@@ -464,18 +738,202 @@ impl<'e> Example<'e> {
     pub fn to_html(&self) -> Html {
         // Custom syntax highlighting with links
         if self.analyze && let Ok(sweet) = self.try_to_analyzed_html().inspect_err(|e|
-            println!("Unable to parse example: {e}")
+            self.builder.config.diagnostics.warn(
+                "example-parse",
+                None,
+                format!("Unable to parse example: {e}"),
+            )
         ) {
             sweet
         }
-        // Otherwise create a regular code block
+        // Otherwise highlight it as a regular code block
         else {
-            HtmlElement::new("pre")
-                .with_child(HtmlElement::new("code")
-                    .with_classes(&["example", "language-cpp"])
-                    .with_text(&self.data)
-                )
-                .into()
+            highlight(self.builder, &self.data, "cpp").unwrap_or_else(|| {
+                HtmlElement::new("pre")
+                    .with_child(HtmlElement::new("code")
+                        .with_classes(&["example", "language-cpp"])
+                        .with_text(&self.data)
+                    )
+                    .into()
+            })
+        }
+    }
+}
+
+/// Why and since when something is deprecated; specified with 
+/// `@deprecated [since] message` or a `[[deprecated("message")]]` attribute
+#[derive(Clone)]
+pub struct Deprecation {
+    pub since: Option<String>,
+    pub message: Option<String>,
+}
+
+impl Deprecation {
+    fn parse(value: Option<String>) -> Self {
+        let Some(value) = value else {
+            return Self { since: None, message: None };
+        };
+        // The first word is the version if it looks like one
+        let (first, rest) = value
+            .split_once(char::is_whitespace)
+            .unwrap_or((&value, ""));
+        let is_version = first
+            .trim_start_matches('v')
+            .starts_with(|c: char| c.is_ascii_digit());
+        if is_version {
+            Self {
+                since: Some(first.to_owned()),
+                message: (!rest.trim().is_empty()).then(|| rest.trim().to_owned()),
+            }
+        }
+        else {
+            Self { since: None, message: Some(value) }
+        }
+    }
+
+    /// Get the deprecation from a doc comment the same way `JSDocComment`
+    /// parses it, without formatting the rest of the comment. Used where
+    /// there's no builder to parse the full comment with
+    pub fn from_comment(raw: &str) -> Option<Self> {
+        let raw = CommentLexer::normalize(raw, &HashMap::new());
+        let mut lexer = CommentLexer::new(&raw);
+        let mut res = None;
+        while let Some(cmd) = lexer.next_command() {
+            let value = lexer.next_value();
+            if cmd.cmd == "deprecated" {
+                res = Some(Self::parse(value));
+            }
+        }
+        res
+    }
+
+    /// The message in the source of a `deprecated("message")` attribute
+    fn attribute_message(src: &str) -> Option<String> {
+        let args = &src[src.find("deprecated")? + "deprecated".len()..];
+        let args = args.trim_start().strip_prefix('(')?.trim_start().strip_prefix('"')?;
+        Some(args[..args.find('"')?].to_owned())
+    }
+
+    /// The source of a declaration up to its name, which is where its
+    /// attributes are, without the body and its members
+    fn declaration_head(entity: &Entity) -> Option<String> {
+        let start = entity.get_range()?.get_start().get_file_location();
+        let name = entity.get_location()?.get_file_location();
+        let contents = start.file?.get_contents()?;
+        contents.get(start.offset as usize..name.offset as usize).map(|s| s.to_owned())
+    }
+
+    /// Get the deprecation from a `[[deprecated]]` attribute. libclang 
+    /// doesn't expose the attribute's message, so it's read from the source
+    /// of the entity's attributes, or the declaration before its name if
+    /// they aren't exposed either
+    fn from_attribute(entity: &Entity) -> Option<Self> {
+        if entity.get_availability() != Availability::Deprecated {
+            return None;
+        }
+        let message = entity
+            .get_children()
+            .into_iter()
+            .filter(|child| child.get_kind() == EntityKind::UnexposedAttr)
+            .filter_map(|attr| attr.extract_source_string())
+            .find_map(|src| Self::attribute_message(&src))
+            .or_else(|| Self::declaration_head(entity).and_then(|src| Self::attribute_message(&src)));
+        Some(Self { since: None, message })
+    }
+
+    /// Get the deprecation of an entity from either its doc comment or its 
+    /// attributes
+    pub fn of(entity: &Entity<'_>, builder: &Builder) -> Option<Self> {
+        JSDocComment::from_entity(entity, builder).and_then(|c| c.deprecated().cloned())
+    }
+
+    /// Format the deprecation message, linking names in code spans to their 
+    /// docs so replacements like "Use `Mod::get` instead" are clickable
+    pub fn fmt_message(
+        &self,
+        builder: &Builder,
+        scope: Option<&Entity>,
+        location: Option<String>,
+    ) -> Option<Html> {
+        let message = self.message.as_ref()?;
+        Some(fmt_markdown(
+            builder,
+            &fmt_doc_links(builder, &link_code_spans(builder, message, scope), scope, location),
+            None::<fn(_) -> _>,
+        ))
+    }
+
+    pub fn fmt_title(&self) -> String {
+        match self.since {
+            Some(ref since) => format!("Deprecated since {since}"),
+            None => String::from("Deprecated"),
+        }
+    }
+}
+
+/// Parameter direction from Doxygen's `@param[in]`, `@param[out]` and
+/// `@param[in,out]`
+#[derive(Clone, Copy, PartialEq)]
+pub enum ParamDirection {
+    In,
+    Out,
+    InOut,
+}
+
+impl ParamDirection {
+    fn from_attrs(attrs: &HashMap<String, Option<String>>) -> Option<Self> {
+        match (attrs.contains_key("in"), attrs.contains_key("out")) {
+            (true, true) => Some(Self::InOut),
+            (true, false) => Some(Self::In),
+            (false, true) => Some(Self::Out),
+            (false, false) => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::In => "in",
+            Self::Out => "out",
+            Self::InOut => "in, out",
+        }
+    }
+}
+
+pub struct Param {
+    pub name: String,
+    pub direction: Option<ParamDirection>,
+    pub description: String,
+}
+
+/// Find the closest method overridden by `entity` that has a doc comment
+fn documented_override<'e>(entity: &Entity<'e>) -> Option<Entity<'e>> {
+    entity
+        .get_overridden_methods()?
+        .into_iter()
+        .find_map(|base| {
+            if base.get_comment().is_some() {
+                Some(base)
+            }
+            else {
+                documented_override(&base)
+            }
+        })
+}
+
+/// A use of a project-defined command from `[comments.commands]`
+struct CustomCommand {
+    cmd: String,
+    param: Option<String>,
+    value: String,
+}
+
+impl CustomCommand {
+    /// The parameter and value as one line of text
+    fn text(&self) -> String {
+        match &self.param {
+            Some(param) if !self.value.is_empty() => format!("{param} {}", self.value),
+            Some(param) => param.clone(),
+            None => self.value.clone(),
         }
     }
 }
@@ -484,11 +942,13 @@ pub struct JSDocComment<'e> {
     /// Description (duh)
     description: Option<String>,
     /// Parameters; specified with @param or @arg
-    params: Vec<(String, String)>,
+    params: Vec<Param>,
     /// Template parameters; specified with @tparam
     tparams: Vec<(String, String)>,
     /// Return value
     returns: Option<String>,
+    /// Specific return values; specified with @retval
+    retvals: Vec<(String, String)>,
     /// What this throws
     throws: Option<String>,
     /// Refer to other doc item(s)
@@ -505,47 +965,173 @@ pub struct JSDocComment<'e> {
     since: Option<String>,
     /// Examples
     examples: Vec<Example<'e>>,
+    /// Groups this item belongs to; specified with @ingroup
+    groups: Vec<String>,
+    /// Group defined by this comment as name and title; specified with
+    /// @defgroup
+    defgroup: Option<(String, String)>,
+    /// Preconditions; specified with @pre
+    pre: Vec<String>,
+    /// Postconditions; specified with @post
+    post: Vec<String>,
+    /// Class invariants; specified with @invariant
+    invariants: Vec<String>,
+    /// Thread safety rules; specified with @threadsafety
+    threadsafety: Option<String>,
+    /// Deprecation notice; specified with @deprecated
+    deprecated: Option<Deprecation>,
+    /// Project-defined commands, in the order they appear
+    custom: Vec<CustomCommand>,
+    /// The overridden method this comment was taken from, if the entity
+    /// itself is undocumented
+    inherited_from: Option<Entity<'e>>,
+    /// How many @copydoc and @inheritdoc commands deep we are, to stop
+    /// comments that copy each other from looping forever
+    copy_depth: usize,
+    /// The entity this comment is attached to, if any
+    entity: Option<Entity<'e>>,
     /// Reference to builder
     builder: &'e Builder<'e>,
 }
 
 impl<'e> JSDocComment<'e> {
     fn parse_mut(mut self, raw: String) -> Self {
+        let raw = CommentLexer::normalize(&raw, &self.builder.config.comments.commands);
         let mut lexer = CommentLexer::new(&raw);
+        let mut seen = HashSet::new();
 
         while let Some(cmd) = lexer.next_command() {
+            // Commands that only keep one value
+            let single = match cmd.cmd.as_str() {
+                "return" | "returns" | "result" => Some("returns"),
+                "throws" | "throw" | "exception" => Some("throws"),
+                "version" => Some("version"),
+                "since" => Some("since"),
+                "threadsafety" | "threadsafe" => Some("threadsafety"),
+                "defgroup" => Some("defgroup"),
+                _ => None,
+            };
+            if let Some(name) = single && !seen.insert(name) {
+                lexer.warnings.push((
+                    "duplicate-command",
+                    format!("Duplicate @{name}, only the last one is used"),
+                ));
+            }
+
             match cmd.cmd.as_str() {
-                "description" | "desc" | "brief" =>
+                "description" | "desc" | "brief" | "details" =>
                 // Empty descriptions shouldn't result in warnings
                 // This does make it so empty @description doesn't warn but eh
                 // good enough
                 {
-                    self.description = lexer.next_value()
+                    // Doxygen comments may have a brief and a detailed
+                    // description, as well as text after the other commands
+                    if let Some(value) = lexer.next_value() {
+                        self.description = Some(match self.description.take() {
+                            Some(desc) => format!("{desc}\n\n{value}"),
+                            None => value,
+                        });
+                    }
                 }
-                "param" | "arg" => self
-                    .params
-                    .push((lexer.param_for(&cmd), lexer.value_for(&cmd))),
+                "param" | "arg" => self.params.push(Param {
+                    direction: ParamDirection::from_attrs(&cmd.attrs),
+                    name: lexer.param_for(&cmd),
+                    description: lexer.value_for(&cmd),
+                }),
                 "tparam" | "targ" => self
                     .tparams
                     .push((lexer.param_for(&cmd), lexer.value_for(&cmd))),
-                "return" | "returns" => self.returns = lexer.value_for(&cmd).into(),
-                "throws" => self.throws = lexer.value_for(&cmd).into(),
-                "see" => self.see.push(lexer.value_for(&cmd)),
-                "note" =>
+                "return" | "returns" | "result" => self.returns = lexer.value_for(&cmd).into(),
+                "retval" => self
+                    .retvals
+                    .push((lexer.param_for(&cmd), lexer.value_for(&cmd))),
+                "throws" | "throw" | "exception" => self.throws = lexer.value_for(&cmd).into(),
+                "see" | "sa" => self.see.push(lexer.value_for(&cmd)),
+                "note" | "remark" | "remarks" =>
                     if cmd.attrs.contains_key("short") {
                         self.short_notes.push(lexer.value_for(&cmd))
                     }
                     else {
                         self.notes.push(lexer.value_for(&cmd))
                     },
-                "warning" | "warn" => self.warnings.push(lexer.value_for(&cmd)),
+                "warning" | "warn" | "attention" => self.warnings.push(lexer.value_for(&cmd)),
                 "version" => self.version = lexer.value_for(&cmd).into(),
                 "since" => self.since = lexer.value_for(&cmd).into(),
-                "example" | "code" => self.examples.push(Example::new(
-                    lexer.value_for(&cmd),
-                    cmd.attrs.contains_key("flash"),
-                    self.builder,
+                "example" | "code" => {
+                    // Examples from a file don't have a body, so any text
+                    // after them is part of the description
+                    let example = match cmd.attrs.get("file") {
+                        Some(Some(file)) => Example::from_file(
+                            file,
+                            cmd.attrs.get("region").cloned().flatten().as_deref(),
+                            cmd.attrs.contains_key("flash"),
+                            self.builder,
+                        ),
+                        _ => Ok(Example::new(
+                            lexer.value_for(&cmd),
+                            cmd.attrs.contains_key("flash"),
+                            self.builder,
+                        )),
+                    };
+                    match example {
+                        Ok(example) => self.examples.push(example),
+                        Err(e) => self.builder.config.diagnostics.error(
+                            "include-file",
+                            self.location(),
+                            e,
+                        ),
+                    }
+                }
+                "ingroup" => self.groups.extend(
+                    lexer.value_for(&cmd)
+                        .split_whitespace()
+                        .map(|s| s.to_owned())
+                ),
+                "defgroup" => self.defgroup = Some((
+                    lexer.param_for(&cmd),
+                    lexer.next_line().unwrap_or_default(),
                 )),
+                "deprecated" => self.deprecated = Some(Deprecation::parse(lexer.next_value())),
+                "pre" => self.pre.push(lexer.value_for(&cmd)),
+                "post" => self.post.push(lexer.value_for(&cmd)),
+                "invariant" => self.invariants.push(lexer.value_for(&cmd)),
+                "threadsafety" | "threadsafe" => self.threadsafety = lexer.value_for(&cmd).into(),
+                "inheritdoc" => {
+                    match self.entity.as_ref().and_then(documented_override) {
+                        Some(base) => self = self.copy_from(&base, &mut lexer.warnings),
+                        None => lexer.warnings.push((
+                            "unresolved-copydoc",
+                            String::from("@inheritdoc on something that doesn't override a documented method"),
+                        )),
+                    }
+                }
+                "copydoc" => {
+                    let target = lexer.param_for(&cmd);
+                    let found = resolve(self.builder, &target.replace('#', "::"), self.entity.as_ref())
+                        .and_then(|t| match t {
+                            Target::Entity(entity) | Target::Member(_, entity) => Some(entity),
+                            _ => None,
+                        });
+                    match found {
+                        Some(entity) => self = self.copy_from(&entity, &mut lexer.warnings),
+                        None => lexer.warnings.push((
+                            "unresolved-copydoc",
+                            format!("Unable to resolve @copydoc target `{target}`"),
+                        )),
+                    }
+                }
+                // Ends a Doxygen \\code block, which has already been eaten
+                "endcode" => {}
+                name if self.builder.config.comments.commands.contains_key(name) => {
+                    let param = self.builder.config.comments.commands[name]
+                        .param
+                        .then(|| lexer.param_for(&cmd));
+                    self.custom.push(CustomCommand {
+                        cmd: cmd.cmd.clone(),
+                        param,
+                        value: lexer.next_value().unwrap_or_default(),
+                    });
+                }
                 // _ => println!("Warning parsing JSDoc comment: Unknown command {cmd}"),
                 _ => {
                     // eat a value even though this is an unknown command
@@ -554,15 +1140,136 @@ impl<'e> JSDocComment<'e> {
             }
         }
 
+        let mut names = HashSet::new();
+        for param in &self.params {
+            if !names.insert(&param.name) {
+                lexer.warnings.push(("duplicate-command", format!("Duplicate @param {}", param.name)));
+            }
+        }
+        let mut names = HashSet::new();
+        for (name, _) in &self.tparams {
+            if !names.insert(name) {
+                lexer.warnings.push(("duplicate-command", format!("Duplicate @tparam {name}")));
+            }
+        }
+
+        for (code, msg) in lexer.warnings {
+            self.builder.config.diagnostics.warn(code, self.location(), msg);
+        }
+
         self
     }
 
+    /// Parse the doc comment of another entity into this one
+    fn copy_from(mut self, entity: &Entity<'e>, warnings: &mut Vec<(&'static str, String)>) -> Self {
+        if self.copy_depth >= 8 {
+            warnings.push((
+                "copydoc-cycle",
+                format!("Documentation copied from `{}` copies itself", entity.full_name().join("::")),
+            ));
+            return self;
+        }
+        let Some(raw) = entity.get_comment() else {
+            warnings.push((
+                "unresolved-copydoc",
+                format!("`{}` has no documentation to copy", entity.full_name().join("::")),
+            ));
+            return self;
+        };
+        self.copy_depth += 1;
+        let mut comment = self.parse_mut(raw);
+        comment.copy_depth -= 1;
+        comment
+    }
+
+    /// Check that the comment matches the signature of the entity it 
+    /// documents
+    fn lint(&self) {
+        let Some(entity) = self.entity else {
+            return;
+        };
+        let warn = |code, msg: String| {
+            self.builder.config.diagnostics.warn(code, self.location(), msg)
+        };
+
+        if matches!(
+            entity.get_kind(),
+            EntityKind::FunctionDecl
+                | EntityKind::Method
+                | EntityKind::Constructor
+                | EntityKind::ConversionFunction
+                | EntityKind::FunctionTemplate
+        ) {
+            let args = entity
+                .get_children()
+                .into_iter()
+                .filter(|c| c.get_kind() == EntityKind::ParmDecl)
+                .filter_map(|c| c.get_name())
+                .collect::<Vec<_>>();
+            for param in &self.params {
+                if !args.contains(&param.name) {
+                    warn("unknown-param", format!("@param {} doesn't match any parameter", param.name));
+                }
+            }
+            if !self.params.is_empty() {
+                for arg in &args {
+                    if !self.params.iter().any(|p| &p.name == arg) {
+                        warn("undocumented-param", format!("Parameter {arg} is not documented"));
+                    }
+                }
+            }
+            if self.returns.is_some()
+                && entity
+                    .get_result_type()
+                    .is_none_or(|ty| ty.get_kind() == TypeKind::Void)
+            {
+                warn(
+                    "returns-on-void",
+                    String::from("@returns on a function that doesn't return anything"),
+                );
+            }
+        }
+
+        if !self.invariants.is_empty()
+            && !matches!(
+                entity.get_kind(),
+                EntityKind::ClassDecl | EntityKind::StructDecl | EntityKind::ClassTemplate
+            )
+        {
+            warn(
+                "misplaced-invariant",
+                String::from("@invariant is only meaningful on classes and structs"),
+            );
+        }
+
+        let targs = entity
+            .get_children()
+            .into_iter()
+            .filter(|c| matches!(
+                c.get_kind(),
+                EntityKind::TemplateTypeParameter
+                    | EntityKind::NonTypeTemplateParameter
+                    | EntityKind::TemplateTemplateParameter
+            ))
+            .filter_map(|c| c.get_name())
+            .collect::<Vec<_>>();
+        for (name, _) in &self.tparams {
+            if !targs.contains(name) {
+                warn(
+                    "unknown-tparam",
+                    format!("@tparam {name} doesn't match any template parameter"),
+                );
+            }
+        }
+    }
+
     pub fn new(builder: &'e Builder<'e>) -> Self {
         Self {
             description: None,
             params: Vec::new(),
             tparams: Vec::new(),
             returns: None,
+            retvals: Vec::new(),
             throws: None,
             see: Vec::new(),
             notes: Vec::new(),
@@ -571,6 +1278,17 @@ impl<'e> JSDocComment<'e> {
             version: None,
             since: None,
             examples: Vec::new(),
+            groups: Vec::new(),
+            defgroup: None,
+            pre: Vec::new(),
+            post: Vec::new(),
+            invariants: Vec::new(),
+            threadsafety: None,
+            deprecated: None,
+            custom: Vec::new(),
+            inherited_from: None,
+            copy_depth: 0,
+            entity: None,
             builder,
         }
     }
@@ -579,11 +1297,270 @@ impl<'e> JSDocComment<'e> {
         Self::new(builder).parse_mut(raw)
     }
 
+    /// Parse the doc comment of an entity, if it has one. Entities that are
+    /// deprecated through an attribute get an empty comment with just the
+    /// deprecation even if they have no doc comment
+    pub fn from_entity(entity: &Entity<'e>, builder: &'e Builder<'e>) -> Option<Self> {
+        let attr = Deprecation::from_attribute(entity);
+        let raw = entity.get_comment();
+        if raw.is_none() && attr.is_none() {
+            return None;
+        }
+        let mut comment = Self::new(builder);
+        comment.entity = Some(*entity);
+        let mut comment = comment.parse_mut(raw.unwrap_or_default());
+        comment.lint();
+        if comment.deprecated.is_none() {
+            comment.deprecated = attr;
+        }
+        Some(comment)
+    }
+
+    /// Documentation for an undocumented method that overrides a documented
+    /// one, which is the documentation of the overridden method
+    pub fn inherited(entity: &Entity<'e>, builder: &'e Builder<'e>) -> Option<Self> {
+        let base = documented_override(entity)?;
+        let mut comment = Self::from_entity(&base, builder)?;
+        comment.inherited_from = Some(base);
+        Some(comment)
+    }
+
+    fn location(&self) -> Option<String> {
+        self.entity
+            .and_then(|e| e.comment_location(self.builder.config.clone()))
+    }
+
+    /// Format text from the comment as markdown with its doc links resolved
+    fn fmt_text(&self, text: &str) -> Html {
+        fmt_markdown(
+            self.builder,
+            &fmt_autolinks(
+                self.builder,
+                &fmt_doc_links(self.builder, text, self.entity.as_ref(), self.location()),
+            ),
+            None::<fn(_) -> _>,
+        )
+    }
+
+    fn fmt_see_link(&self, target: &str) -> Html {
+        let resolved = resolve(self.builder, target, self.entity.as_ref()).and_then(|t| {
+            Some((t.href(self.builder.config.clone())?, t.is_external()))
+        });
+        let Some((href, external)) = resolved else {
+            self.builder.config.diagnostics.warn(
+                "unresolved-see",
+                self.location(),
+                format!("Unable to resolve @see target `{target}`"),
+            );
+            return HtmlElement::new("code").with_text(target).into();
+        };
+        HtmlElement::new("a")
+            .with_attr("href", &href)
+            .with_attr_opt(
+                "onclick",
+                (!external).then(|| format!("return navigate('{href}')")),
+            )
+            .with_child(HtmlElement::new("code").with_text(target))
+            .into()
+    }
+
+    fn fmt_see(&self) -> Option<Html> {
+        (!self.see.is_empty()).then(|| {
+            HtmlElement::new("section")
+                .with_classes(&["params", "see"])
+                .with_child(Html::span(&["title"], "See also"))
+                .with_child(
+                    HtmlElement::new("div").with_class("grid").with_children(
+                        self.see
+                            .iter()
+                            .flat_map(|see| {
+                                // The first word is the target and the rest is
+                                // an optional description
+                                let (target, desc) = see
+                                    .split_once(char::is_whitespace)
+                                    .unwrap_or((see, ""));
+                                let target = target.trim_end_matches([',', '.', ';']);
+                                vec![self.fmt_see_link(target), self.fmt_text(desc.trim())]
+                            })
+                            .collect(),
+                    ),
+                )
+                .into()
+        })
+    }
+
+    fn fmt_deprecated(&self) -> Option<Html> {
+        let deprecated = self.deprecated.as_ref()?;
+        Some(
+            HtmlElement::new("blockquote")
+                .with_class("deprecated")
+                .with_child(Html::p(format!("⛔ {}", deprecated.fmt_title())))
+                .with_child_opt(deprecated.fmt_message(
+                    self.builder,
+                    self.entity.as_ref(),
+                    self.location(),
+                ))
+                .into(),
+        )
+    }
+
+    fn fmt_contract(&self, class: &str, title: &str, items: &[String]) -> Option<Html> {
+        (!items.is_empty()).then(|| {
+            HtmlElement::new("section")
+                .with_classes(&["contract", class])
+                .with_child(Html::span(&["title"], title))
+                .with_child(
+                    HtmlElement::new("ul").with_children(
+                        items
+                            .iter()
+                            .map(|item| HtmlElement::new("li").with_text(item).into())
+                            .collect(),
+                    ),
+                )
+                .into()
+        })
+    }
+
+    /// Render the project-defined commands of one kind. Tags and callouts
+    /// are rendered once per use, sections and lists once per command
+    fn fmt_custom(&self, kind: CommandKind) -> Vec<Html> {
+        let mut names = Vec::<&String>::new();
+        for custom in &self.custom {
+            if !names.contains(&&custom.cmd) {
+                names.push(&custom.cmd);
+            }
+        }
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let def = self.builder.config.comments.commands.get(name)?;
+                let uses = self.custom.iter().filter(|c| &c.cmd == name).collect::<Vec<_>>();
+                (def.kind == kind).then(|| Self::fmt_custom_command(def, &uses))
+            })
+            .flatten()
+            .collect()
+    }
+
+    fn fmt_custom_command(def: &CommandDef, uses: &[&CustomCommand]) -> Vec<Html> {
+        match def.kind {
+            CommandKind::Tag => uses
+                .iter()
+                .map(|custom| {
+                    let text = custom.text();
+                    Html::p(if text.is_empty() {
+                        def.title.clone()
+                    }
+                    else {
+                        format!("{}: {text}", def.title)
+                    })
+                })
+                .collect(),
+            CommandKind::Callout => uses
+                .iter()
+                .map(|custom| {
+                    HtmlElement::new("blockquote")
+                        .with_class(def.class.as_deref().unwrap_or("info"))
+                        .with_child(Html::p(format!(
+                            "{}{}: {}",
+                            def.icon.as_ref().map(|i| format!("{i} ")).unwrap_or_default(),
+                            def.title,
+                            custom.text()
+                        )))
+                        .into()
+                })
+                .collect(),
+            CommandKind::Section => vec![
+                HtmlElement::new("section")
+                    .with_classes(&["params", "custom"])
+                    .with_class_opt(def.class.as_deref())
+                    .with_child(Html::span(&["title"], &def.title))
+                    .with_child::<Html>(if def.param {
+                        HtmlElement::new("div")
+                            .with_class("grid")
+                            .with_children(
+                                uses.iter()
+                                    .flat_map(|custom| {
+                                        vec![
+                                            Html::p(custom.param.clone().unwrap_or_default()),
+                                            Html::div(custom.value.clone()),
+                                        ]
+                                    })
+                                    .collect(),
+                            )
+                            .into()
+                    }
+                    else {
+                        HtmlList::new(uses.iter().map(|custom| Html::div(&custom.value)).collect())
+                            .into()
+                    })
+                    .into(),
+            ],
+            CommandKind::List => vec![
+                HtmlElement::new("section")
+                    .with_classes(&["contract", "custom"])
+                    .with_class_opt(def.class.as_deref())
+                    .with_child(Html::span(&["title"], &def.title))
+                    .with_child(
+                        HtmlElement::new("ul").with_children(
+                            uses.iter()
+                                .map(|custom| HtmlElement::new("li").with_text(custom.text()).into())
+                                .collect(),
+                        ),
+                    )
+                    .into(),
+            ],
+        }
+    }
+
+    /// Short version of the thread safety rules to show next to signatures,
+    /// which is the first sentence
+    pub fn thread_tag(&self) -> Option<String> {
+        let rules = self.threadsafety.as_ref()?;
+        let first = rules
+            .split_once(". ")
+            .map(|(first, _)| first)
+            .unwrap_or(rules)
+            .lines()
+            .next()?
+            .trim()
+            .trim_end_matches('.');
+        (!first.is_empty()).then(|| first.to_owned())
+    }
+
+    fn fmt_inherited(&self) -> Option<Html> {
+        let base = self.inherited_from?;
+        let name = base.full_name().join("::");
+        let href = base
+            .get_semantic_parent()
+            .and_then(|class| Target::Member(class, base).href(self.builder.config.clone()));
+        Some(
+            HtmlElement::new("p")
+                .with_class("inherited")
+                .with_text("Documentation inherited from ")
+                .with_child::<Html>(match href {
+                    Some(href) => HtmlElement::new("a")
+                        .with_attr("href", &href)
+                        .with_attr("onclick", format!("return navigate('{href}')"))
+                        .with_child(HtmlElement::new("code").with_text(&name))
+                        .into(),
+                    None => HtmlElement::new("code").with_text(&name).into(),
+                })
+                .into(),
+        )
+    }
+
     pub fn to_html(&self, include_examples: bool) -> Html {
         HtmlList::new(vec![HtmlElement::new("div")
             .with_class("description")
-            .with_child_opt(
-                if self.version.is_some() || self.since.is_some() || !self.short_notes.is_empty() {
+            .with_child_opt(self.fmt_inherited())
+            .with_child_opt(self.fmt_deprecated())
+            .with_child_opt({
+                let custom_tags = self.fmt_custom(CommandKind::Tag);
+                if self.version.is_some()
+                    || self.since.is_some()
+                    || !self.short_notes.is_empty()
+                    || !custom_tags.is_empty()
+                {
                     HtmlElement::new("div")
                         .with_class("tags")
                         .with_child_opt(
@@ -595,17 +1572,14 @@ impl<'e> JSDocComment<'e> {
                         .with_children(
                             self.short_notes.iter().map(Html::p).collect()
                         )
+                        .with_children(custom_tags)
                         .into()
                 } else { None }
-            )
+            })
             .with_child(
                 self.description
                     .as_ref()
-                    .map(|d| fmt_markdown(
-                        self.builder,
-                        &fmt_autolinks(self.builder, d, None),
-                        None::<fn(_) -> _>
-                    ))
+                    .map(|d| self.fmt_text(d))
                     .unwrap_or(Html::span(&["no-desc"], "No description provided")),
             )
             .with_child_opt(
@@ -618,7 +1592,15 @@ impl<'e> JSDocComment<'e> {
                                 self.params
                                     .iter()
                                     .flat_map(|param| {
-                                        vec![Html::p(param.0.clone()), Html::div(param.1.clone())]
+                                        vec![
+                                            HtmlElement::new("p")
+                                                .with_text(&param.name)
+                                                .with_child_opt(param.direction.map(|dir| {
+                                                    Html::span(&["direction"], &format!("[{}]", dir.name()))
+                                                }))
+                                                .into(),
+                                            self.fmt_text(&param.description),
+                                        ]
                                     })
                                     .collect(),
                             ),
@@ -635,7 +1617,7 @@ impl<'e> JSDocComment<'e> {
                                 self.tparams
                                     .iter()
                                     .flat_map(|tparam| {
-                                        vec![Html::p(tparam.0.clone()), Html::div(tparam.1.clone())]
+                                        vec![Html::p(tparam.0.clone()), self.fmt_text(&tparam.1)]
                                     })
                                     .collect(),
                             ),
@@ -646,22 +1628,51 @@ impl<'e> JSDocComment<'e> {
                 HtmlElement::new("section")
                     .with_classes(&["params", "returns", "grid"])
                     .with_child(Html::span(&["title"], "Return value"))
-                    .with_child(Html::div(ret.clone()))
+                    .with_child(self.fmt_text(ret))
             }))
+            .with_child_opt(
+                (!self.retvals.is_empty()).then_some(
+                    HtmlElement::new("section")
+                        .with_classes(&["params", "retvals"])
+                        .with_child(Html::span(&["title"], "Return values"))
+                        .with_child(
+                            HtmlElement::new("div").with_class("grid").with_children(
+                                self.retvals
+                                    .iter()
+                                    .flat_map(|retval| {
+                                        vec![Html::p(retval.0.clone()), self.fmt_text(&retval.1)]
+                                    })
+                                    .collect(),
+                            ),
+                        ),
+                ),
+            )
             .with_child_opt(self.throws.as_ref().map(|ret| {
                 HtmlElement::new("section")
                     .with_classes(&["params", "throws", "grid"])
                     .with_child(Html::span(&["title"], "Exceptions"))
-                    .with_child(Html::div(ret.clone()))
+                    .with_child(self.fmt_text(ret))
+            }))
+            .with_child_opt(self.fmt_contract("pre", "Preconditions", &self.pre))
+            .with_child_opt(self.fmt_contract("post", "Postconditions", &self.post))
+            .with_child_opt(self.fmt_contract("invariant", "Invariants", &self.invariants))
+            .with_child_opt(self.threadsafety.as_ref().map(|rules| {
+                HtmlElement::new("section")
+                    .with_classes(&["contract", "threadsafety"])
+                    .with_child(Html::span(&["title"], "Thread safety"))
+                    .with_child(Html::div(rules))
             }))
-            // todo: see
+            .with_children(self.fmt_custom(CommandKind::Section))
+            .with_children(self.fmt_custom(CommandKind::List))
+            .with_child_opt(self.fmt_see())
+            .with_children(self.fmt_custom(CommandKind::Callout))
             .with_children(
                 self.notes
                     .iter()
                     .map(|note| {
                         HtmlElement::new("blockquote")
                             .with_class("info")
-                            .with_child(Html::p(format!("ℹ {}", note)))
+                            .with_child(self.fmt_text(&format!("ℹ {note}")))
                             .into()
                     })
                     .collect(),
@@ -672,7 +1683,7 @@ impl<'e> JSDocComment<'e> {
                     .map(|warning| {
                         HtmlElement::new("blockquote")
                             .with_class("warning")
-                            .with_child(Html::p(format!("⚠️ {}", warning)))
+                            .with_child(self.fmt_text(&format!("⚠️ {warning}")))
                             .into()
                     })
                     .collect(),
@@ -692,4 +1703,187 @@ impl<'e> JSDocComment<'e> {
     pub fn examples(&self) -> &Vec<Example> {
         &self.examples
     }
+
+    pub fn deprecated(&self) -> Option<&Deprecation> {
+        self.deprecated.as_ref()
+    }
+
+    pub fn groups(&self) -> &Vec<String> {
+        &self.groups
+    }
+
+    pub fn defgroup(&self) -> Option<&(String, String)> {
+        self.defgroup.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every command in a comment with its value, as the lexer reads them
+    fn lex(raw: &str) -> Vec<(String, Option<String>)> {
+        let raw = CommentLexer::normalize(raw, &HashMap::new());
+        let mut lexer = CommentLexer::new(&raw);
+        let mut res = Vec::new();
+        while let Some(cmd) = lexer.next_command() {
+            res.push((cmd.cmd, lexer.next_value()));
+        }
+        res
+    }
+
+    fn cmd(name: &str, value: Option<&str>) -> (String, Option<String>) {
+        (name.to_owned(), value.map(|v| v.to_owned()))
+    }
+
+    #[test]
+    fn escaped_at_is_not_a_command() {
+        assert_eq!(
+            lex("Use \\@param to document parameters\n@param x The value"),
+            vec![
+                cmd("description", Some("Use @param to document parameters")),
+                cmd("param", Some("x The value")),
+            ],
+        );
+        assert_eq!(
+            lex("Write @@return in text"),
+            vec![cmd("description", Some("Write @return in text"))],
+        );
+    }
+
+    #[test]
+    fn commands_in_code_spans_are_literal() {
+        assert_eq!(
+            lex("Call `@param` and ``\\c x`` here"),
+            vec![cmd("description", Some("Call `@param` and ``\\c x`` here"))],
+        );
+        assert_eq!(
+            lex("Escape with `\\n` and \\c word"),
+            vec![cmd("description", Some("Escape with `\\n` and `word`"))],
+        );
+    }
+
+    #[test]
+    fn commands_in_fenced_blocks_are_literal() {
+        assert_eq!(
+            lex("Example:\n```\n@param x\n```"),
+            vec![cmd("description", Some("Example:\n```\n@param x\n```"))],
+        );
+    }
+
+    #[test]
+    fn code_blocks_are_literal() {
+        assert_eq!(
+            lex("\\code\nint x = a \\b c;\n\\endcode"),
+            vec![cmd("code", Some("int x = a \\b c;")), cmd("endcode", None)],
+        );
+        assert_eq!(
+            lex("@code int x = @p; @endcode"),
+            vec![cmd("code", Some("int x = @p;")), cmd("endcode", None)],
+        );
+    }
+
+    #[test]
+    fn code_from_file_has_no_code_block() {
+        let cmds = lex("@code[file = examples/a.cpp] Uses \\c x");
+        assert_eq!(cmds.len(), 1);
+        assert_eq!(cmds[0].0, "code");
+        assert_eq!(cmds[0].1.as_deref(), Some("Uses `x`"));
+    }
+
+    #[test]
+    fn unknown_backslash_commands_are_text() {
+        assert_eq!(
+            lex("Splits the string on \\n or \\t characters"),
+            vec![cmd("description", Some("Splits the string on \\n or \\t characters"))],
+        );
+        assert_eq!(
+            lex("@param sep Defaults to \\n if not given"),
+            vec![cmd("param", Some("sep Defaults to \\n if not given"))],
+        );
+    }
+
+    #[test]
+    fn commands_in_words_are_text() {
+        assert_eq!(
+            lex("mail foo@b bar"),
+            vec![cmd("description", Some("mail foo@b bar"))],
+        );
+        assert_eq!(
+            lex("Costs 5 @ most\n@returns x"),
+            vec![cmd("description", Some("Costs 5 @ most")), cmd("returns", Some("x"))],
+        );
+    }
+
+    #[test]
+    fn examples_are_literal() {
+        assert_eq!(
+            lex("@example\n#define X(a) \\\nX(1)\n@returns Nothing"),
+            vec![
+                cmd("example", Some("#define X(a) \\\nX(1)")),
+                cmd("returns", Some("Nothing")),
+            ],
+        );
+        assert_eq!(
+            lex("@example\nprintf(\"%s\\n\", s); // escape \\b here"),
+            vec![cmd("example", Some("printf(\"%s\\n\", s); // escape \\b here"))],
+        );
+    }
+
+    #[test]
+    fn unclosed_code_span_is_not_literal() {
+        assert_eq!(
+            lex("A lone ` tick\n@param x The value"),
+            vec![
+                cmd("description", Some("A lone ` tick")),
+                cmd("param", Some("x The value")),
+            ],
+        );
+    }
+
+    fn deprecation(value: Option<&str>) -> (Option<String>, Option<String>) {
+        let dep = Deprecation::parse(value.map(|v| v.to_owned()));
+        (dep.since, dep.message)
+    }
+
+    #[test]
+    fn deprecation_since_version() {
+        assert_eq!(
+            deprecation(Some("3.2 Use `Mod::get` instead")),
+            (Some("3.2".into()), Some("Use `Mod::get` instead".into())),
+        );
+        assert_eq!(deprecation(Some("v4.0.0")), (Some("v4.0.0".into()), None));
+    }
+
+    #[test]
+    fn deprecation_without_version() {
+        assert_eq!(deprecation(None), (None, None));
+        assert_eq!(
+            deprecation(Some("Use `Mod::get` instead")),
+            (None, Some("Use `Mod::get` instead".into())),
+        );
+        // Words starting with a v aren't versions unless a number follows
+        assert_eq!(
+            deprecation(Some("version 2 replaces this")),
+            (None, Some("version 2 replaces this".into())),
+        );
+    }
+
+    #[test]
+    fn deprecation_from_comment() {
+        let dep = Deprecation::from_comment("/**\n * Does things\n * @deprecated 2.0 Use bar\n */");
+        assert_eq!(dep.and_then(|d| d.since), Some("2.0".into()));
+        assert!(Deprecation::from_comment("/// Mentions `@deprecated` in code").is_none());
+        assert!(Deprecation::from_comment("/// Mentions \\@deprecated").is_none());
+    }
+
+    #[test]
+    fn deprecation_attribute_message() {
+        assert_eq!(Deprecation::attribute_message("deprecated(\"Use bar\")"), Some("Use bar".into()));
+        assert_eq!(
+            Deprecation::attribute_message("__attribute__((deprecated ( \"Use bar\" )))"),
+            Some("Use bar".into()),
+        );
+        assert_eq!(Deprecation::attribute_message("deprecated"), None);
+    }
 }
@@ -0,0 +1,238 @@
+use std::{collections::BTreeMap, fs, sync::Arc};
+
+use clang::Entity;
+use serde::Serialize;
+
+use crate::{
+    html::{Html, HtmlElement, HtmlText},
+    url::UrlPath,
+};
+
+use super::{
+    builder::Builder,
+    namespace::CppItem,
+    traits::{ASTEntry, Access, BuildResult, EntityMethods, Entry, Include, NavItem, OutputEntry},
+};
+
+#[derive(Default, Clone, Copy, Serialize)]
+pub struct CoverageCount {
+    documented: usize,
+    total: usize,
+}
+
+impl CoverageCount {
+    fn add(&mut self, documented: bool) {
+        self.total += 1;
+        if documented {
+            self.documented += 1;
+        }
+    }
+
+    /// Percentage of documented entities. Nothing to document counts as
+    /// fully documented
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        }
+        else {
+            self.documented as f64 / self.total as f64 * 100.0
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct UndocumentedItem {
+    name: String,
+    kind: &'static str,
+    location: Option<String>,
+}
+
+/// How much of the public API has doc comments, broken down by namespace,
+/// file and entity kind
+#[derive(Serialize)]
+pub struct Coverage {
+    total: CoverageCount,
+    namespaces: BTreeMap<String, CoverageCount>,
+    files: BTreeMap<String, CoverageCount>,
+    kinds: BTreeMap<&'static str, CoverageCount>,
+    undocumented: Vec<UndocumentedItem>,
+}
+
+impl Coverage {
+    fn add(&mut self, entity: &Entity, kind: &'static str, builder: &Builder) {
+        let documented = entity.get_comment().is_some();
+        let mut scope = entity.full_name();
+        let name = scope.join("::");
+        // Member functions are counted towards the namespace of their class
+        scope.pop();
+        if kind == "member function" {
+            scope.pop();
+        }
+        let namespace = if scope.is_empty() {
+            String::from("(global)")
+        }
+        else {
+            scope.join("::")
+        };
+        let file = entity
+            .definition_file()
+            .map(|f| {
+                f.strip_prefix(&builder.config.input_dir)
+                    .unwrap_or(&f)
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .unwrap_or(String::from("(unknown)"));
+
+        self.total.add(documented);
+        self.namespaces.entry(namespace).or_default().add(documented);
+        self.files.entry(file).or_default().add(documented);
+        self.kinds.entry(kind).or_default().add(documented);
+        if !documented {
+            self.undocumented.push(UndocumentedItem {
+                name,
+                kind,
+                location: entity.comment_location(builder.config.clone()),
+            });
+        }
+    }
+
+    fn collect(&mut self, item: &CppItem, builder: &Builder) {
+        match item {
+            CppItem::Namespace(ns) => {
+                for entry in ns.entries.values() {
+                    self.collect(entry, builder);
+                }
+            }
+            CppItem::Class(_) | CppItem::Struct(_) => {
+                let kind = if matches!(item, CppItem::Class(_)) { "class" } else { "struct" };
+                self.add(item.entity(), kind, builder);
+                for fun in item.entity().get_member_functions(Access::Public, Include::All) {
+                    self.add(&fun, "member function", builder);
+                }
+            }
+            CppItem::Function(_) => self.add(item.entity(), "function", builder),
+        }
+    }
+
+    pub fn from_builder(builder: &Builder) -> Self {
+        let mut coverage = Self {
+            total: CoverageCount::default(),
+            namespaces: BTreeMap::new(),
+            files: BTreeMap::new(),
+            kinds: BTreeMap::new(),
+            undocumented: Vec::new(),
+        };
+        for item in builder.root.entries.values() {
+            coverage.collect(item, builder);
+        }
+        coverage
+    }
+
+    pub fn percent(&self) -> f64 {
+        self.total.percent()
+    }
+
+    fn fmt_table<K: AsRef<str>>(title: &str, counts: &BTreeMap<K, CoverageCount>) -> Html {
+        HtmlElement::new("table")
+            .with_class("coverage")
+            .with_child(
+                HtmlElement::new("tr")
+                    .with_child(HtmlElement::new("th").with_text(title))
+                    .with_child(HtmlElement::new("th").with_text("Documented"))
+                    .with_child(HtmlElement::new("th").with_text("Coverage")),
+            )
+            .with_children(
+                counts
+                    .iter()
+                    .map(|(name, count)| {
+                        HtmlElement::new("tr")
+                            .with_child(HtmlElement::new("td").with_text(name.as_ref()))
+                            .with_child(
+                                HtmlElement::new("td")
+                                    .with_text(format!("{}/{}", count.documented, count.total)),
+                            )
+                            .with_child(
+                                HtmlElement::new("td")
+                                    .with_text(format!("{:.1}%", count.percent())),
+                            )
+                            .into()
+                    })
+                    .collect(),
+            )
+            .into()
+    }
+
+    fn fmt_undocumented(&self) -> Html {
+        HtmlElement::new("ul")
+            .with_class("undocumented")
+            .with_children(
+                self.undocumented
+                    .iter()
+                    .map(|item| {
+                        HtmlElement::new("li")
+                            .with_child(HtmlElement::new("code").with_text(&item.name))
+                            .with_child(Html::span(&["kind"], item.kind))
+                            .with_child_opt(
+                                item.location.as_ref().map(|l| Html::span(&["location"], l)),
+                            )
+                            .into()
+                    })
+                    .collect(),
+            )
+            .into()
+    }
+}
+
+impl<'e> Entry<'e> for Coverage {
+    fn name(&self) -> String {
+        String::from("Coverage")
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::part("coverage")
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        fs::write(
+            builder.config.output_dir.join("coverage.json"),
+            serde_json::to_string_pretty(self)
+                .map_err(|e| format!("Unable to serialize coverage: {e}"))?,
+        )
+        .map_err(|e| format!("Unable to save coverage.json: {e}"))?;
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("percent", false)), Vec::new())
+    }
+}
+
+impl<'e> OutputEntry<'e> for Coverage {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+        (
+            builder.config.templates.coverage.clone(),
+            vec![
+                ("title", HtmlText::new("Documentation coverage").into()),
+                (
+                    "total",
+                    HtmlText::new(format!(
+                        "{}/{} public entities documented ({:.1}%)",
+                        self.total.documented,
+                        self.total.total,
+                        self.percent()
+                    ))
+                    .into(),
+                ),
+                ("kinds", Self::fmt_table("Kind", &self.kinds)),
+                ("namespaces", Self::fmt_table("Namespace", &self.namespaces)),
+                ("files", Self::fmt_table("File", &self.files)),
+                ("undocumented", self.fmt_undocumented()),
+            ],
+        )
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!("Documentation coverage of {}", builder.config.project.name)
+    }
+}
@@ -0,0 +1,150 @@
+use std::{collections::HashMap, sync::Arc};
+
+use clang::Entity;
+
+use crate::{
+    html::{Html, HtmlElement, HtmlText},
+    url::UrlPath,
+    versions::compare_versions,
+};
+
+use super::{
+    builder::Builder,
+    comment::Deprecation,
+    namespace::CppItem,
+    resolve::Target,
+    traits::{ASTEntry, Access, BuildResult, EntityMethods, Entry, Include, NavItem, OutputEntry},
+};
+
+/// A page listing every deprecated entity, grouped by the version they were
+/// deprecated in
+#[derive(Default)]
+pub struct DeprecatedList<'e> {
+    entities: Vec<Entity<'e>>,
+}
+
+impl<'e> DeprecatedList<'e> {
+    fn collect(item: &CppItem<'e>, out: &mut Vec<Entity<'e>>) {
+        match item {
+            CppItem::Namespace(ns) => {
+                for entry in ns.entries.values() {
+                    Self::collect(entry, out);
+                }
+            }
+            CppItem::Class(_) | CppItem::Struct(_) => {
+                if item.entity().is_deprecated() {
+                    out.push(*item.entity());
+                }
+                out.extend(
+                    item.entity()
+                        .get_member_functions(Access::All, Include::All)
+                        .into_iter()
+                        .filter(|fun| fun.is_deprecated()),
+                );
+            }
+            CppItem::Function(_) => {
+                if item.entity().is_deprecated() {
+                    out.push(*item.entity());
+                }
+            }
+        }
+    }
+
+    pub fn from_builder(builder: &Builder<'e>) -> Self {
+        let mut entities = Vec::new();
+        for item in builder.root.entries.values() {
+            Self::collect(item, &mut entities);
+        }
+        Self { entities }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    fn fmt_item(entity: &Entity<'e>, deprecation: &Deprecation, builder: &'e Builder<'e>) -> Html {
+        let name = entity.full_name().join("::");
+        HtmlElement::new("div")
+            .with_class("deprecated-item")
+            .with_child::<Html>(match Target::of(entity).and_then(|t| t.href(builder.config.clone())) {
+                Some(url) => HtmlElement::new("a")
+                    .with_attr("href", &url)
+                    .with_attr("onclick", format!("return navigate('{url}')"))
+                    .with_child(HtmlElement::new("code").with_text(&name))
+                    .into(),
+                None => HtmlElement::new("code").with_text(&name).into(),
+            })
+            .with_child_opt(deprecation.fmt_message(
+                builder,
+                Some(entity),
+                entity.comment_location(builder.config.clone()),
+            ))
+            .into()
+    }
+}
+
+impl<'e> Entry<'e> for DeprecatedList<'e> {
+    fn name(&self) -> String {
+        String::from("Deprecated")
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::part("deprecated")
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("slash", false)), Vec::new())
+    }
+}
+
+impl<'e> OutputEntry<'e> for DeprecatedList<'e> {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+        // Group by version, with things that don't say when they were
+        // deprecated last
+        let mut versions = HashMap::<Option<String>, Vec<Html>>::new();
+        for entity in &self.entities {
+            let deprecation = Deprecation::of(entity, builder)
+                .unwrap_or(Deprecation { since: None, message: None });
+            versions
+                .entry(deprecation.since.clone())
+                .or_default()
+                .push(Self::fmt_item(entity, &deprecation, builder));
+        }
+        let unversioned = versions.remove(&None);
+        let mut versions = versions.into_iter().collect::<Vec<_>>();
+        versions.sort_by(|(a, _), (b, _)| {
+            compare_versions(a.as_deref().unwrap_or_default(), b.as_deref().unwrap_or_default())
+        });
+        let sections = versions
+            .into_iter()
+            .map(|(since, items)| (format!("Since {}", since.unwrap_or_default()), items))
+            .chain(unversioned.map(|items| (String::from("Unspecified version"), items)))
+            .map(|(title, items)| {
+                HtmlElement::new("section")
+                    .with_class("deprecated-version")
+                    .with_child(HtmlElement::new("h2").with_text(title))
+                    .with_children(items)
+                    .into()
+            })
+            .collect::<Vec<_>>();
+
+        (
+            builder.config.templates.deprecated.clone(),
+            vec![
+                ("title", HtmlText::new("Deprecated APIs").into()),
+                ("content", HtmlElement::new("div").with_children(sections).into()),
+            ],
+        )
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!("Deprecated APIs in {}", builder.config.project.name)
+    }
+}
@@ -0,0 +1,179 @@
+use std::{fs, path::PathBuf};
+
+use clang::{diagnostic::Severity, Entity, EntityKind};
+
+use super::{
+    builder::Builder,
+    comment::JSDocComment,
+    namespace::CppItem,
+    traits::{ASTEntry, EntityMethods},
+    snippet::expand_includes,
+    tutorial::TutorialFolder,
+};
+
+/// Write `data` to a new temporary source file in the output directory so
+/// it can be parsed with the builder's index
+pub fn write_temp_source(builder: &Builder, data: &str) -> Result<PathBuf, String> {
+    let mut num = 0;
+    let path = loop {
+        let path = builder
+            .config
+            .output_dir
+            .join(format!("_example_{num}.cpp"));
+        if !path.exists() {
+            break path;
+        }
+        num += 1;
+    };
+    fs::write(&path, data).map_err(|e| format!("Unable to write example: {e}"))?;
+    Ok(path)
+}
+
+/// Compile an example with the project's headers included and compile args,
+/// returning the errors in it as line numbers relative to the example and
+/// messages
+fn compile(builder: &Builder, code: &str, wrap: bool) -> Result<Vec<(u32, String)>, String> {
+    let mut prelude = String::new();
+    for hdr in builder.config.all_includes() {
+        prelude += &format!("#include <{}>\n", hdr.to_string_lossy());
+    }
+    // Snippets are usually statements, which only compile inside a function
+    if wrap {
+        prelude += "void flash_example() {\n";
+    }
+    let offset = prelude.lines().count() as u32;
+    let source = format!("{prelude}{code}\n{}", if wrap { "}\n" } else { "" });
+
+    let path = write_temp_source(builder, &source)?;
+    let unit = builder
+        .index
+        .parser(&path)
+        .arguments(builder.args)
+        .parse()
+        .map_err(|e| e.to_string());
+    let errors = unit.map(|unit| {
+        unit.get_diagnostics()
+            .into_iter()
+            .filter(|diag| diag.get_severity() >= Severity::Error)
+            .filter_map(|diag| {
+                let loc = diag.get_location().get_file_location();
+                // Errors in the headers aren't the example's fault
+                (loc.file?.get_path() == path && loc.line > offset)
+                    .then(|| (loc.line - offset, diag.get_text()))
+            })
+            .collect()
+    });
+
+    // We don't really care if we can remove the file or not
+    drop(fs::remove_file(path));
+
+    errors
+}
+
+/// Compile an example and report any errors in it. Examples are first tried
+/// as-is and then wrapped in a function, and only fail if both fail
+fn check(builder: &Builder, code: &str, origin: &str, location: Option<String>) {
+    let errors = match compile(builder, code, false) {
+        Ok(errors) if !errors.is_empty() => match compile(builder, code, true) {
+            Ok(wrapped) if wrapped.is_empty() => Ok(wrapped),
+            _ => Ok(errors),
+        },
+        other => other,
+    };
+    match errors {
+        Ok(errors) => {
+            for (line, msg) in errors {
+                builder.config.diagnostics.error(
+                    "example",
+                    location.clone(),
+                    format!("Example in {origin} doesn't compile: line {line}: {msg}"),
+                );
+            }
+        }
+        Err(e) => builder.config.diagnostics.error(
+            "example",
+            location,
+            format!("Unable to parse example in {origin}: {e}"),
+        ),
+    }
+}
+
+fn check_entity<'e>(entity: &Entity<'e>, builder: &'e Builder<'e>) {
+    if let Some(comment) = JSDocComment::from_entity(entity, builder) {
+        let origin = format!("`{}`", entity.full_name().join("::"));
+        for example in comment.examples() {
+            check(builder, example.code(), &origin, entity.comment_location(builder.config.clone()));
+        }
+    }
+    if matches!(
+        entity.get_kind(),
+        EntityKind::ClassDecl | EntityKind::StructDecl | EntityKind::ClassTemplate
+    ) {
+        for child in entity.get_children() {
+            check_entity(&child, builder);
+        }
+    }
+}
+
+fn check_item<'e>(item: &CppItem<'e>, builder: &'e Builder<'e>) {
+    match item {
+        CppItem::Namespace(ns) => {
+            for entry in ns.entries.values() {
+                check_item(entry, builder);
+            }
+        }
+        _ => check_entity(item.entity(), builder),
+    }
+}
+
+/// Find the C++ code blocks in a markdown file as the line the code starts on
+/// and the code
+fn cpp_blocks(markdown: &str) -> Vec<(usize, String)> {
+    let mut blocks = Vec::new();
+    // Other code blocks are skipped over with no code
+    let mut current: Option<(usize, &str, Option<String>)> = None;
+    for (num, line) in markdown.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some((start, fence, code)) = current.take() {
+            if trimmed.starts_with(fence) {
+                blocks.extend(code.map(|code| (start, code)));
+            }
+            else {
+                current = Some((start, fence, code.map(|code| code + line + "\n")));
+            }
+        }
+        else if let Some(fence) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+            let lang = trimmed[fence.len()..].split_whitespace().next().unwrap_or_default();
+            let is_cpp = matches!(lang, "cpp" | "c++" | "cxx" | "hpp");
+            current = Some((num + 2, fence, is_cpp.then(String::new)));
+        }
+    }
+    blocks
+}
+
+fn check_tutorials(folder: &TutorialFolder, builder: &Builder) {
+    let Some(ref tutorials) = builder.config.tutorials else {
+        return;
+    };
+    for (path, content) in folder.sources() {
+        let file = tutorials.dir.join(path.to_pathbuf());
+        let location = Some(format!("{}", file.to_string_lossy()));
+        for (line, code) in cpp_blocks(&content) {
+            check(
+                builder,
+                &expand_includes(&builder.config, &code, location.clone()),
+                &format!("tutorial {path}"),
+                Some(format!("{}:{line}", file.to_string_lossy())),
+            );
+        }
+    }
+}
+
+/// Compile every `@example` in doc comments and every C++ code block in
+/// tutorials, and report the ones that don't compile as errors
+pub fn check_examples<'e>(builder: &'e Builder<'e>) {
+    for item in builder.root.entries.values() {
+        check_item(item, builder);
+    }
+    check_tutorials(&builder.tutorials, builder);
+}
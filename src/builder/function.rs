@@ -36,6 +36,7 @@ impl<'e> Entry<'e> for Function<'e> {
 
     fn nav(&self) -> NavItem {
         NavItem::new_link(&self.name(), self.url(), Some(("code", true)), Vec::new())
+            .deprecated(self.entity.is_deprecated())
     }
 }
 
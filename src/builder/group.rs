@@ -0,0 +1,324 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::Path,
+    sync::Arc,
+};
+
+use clang::Entity;
+
+use crate::{
+    html::{Html, HtmlText},
+    url::UrlPath,
+};
+
+use super::{
+    builder::Builder,
+    comment::{has_command, JSDocComment},
+    markdown::fmt_markdown,
+    namespace::{CppItem, CppItemKind},
+    resolve::fmt_doc_links,
+    shared::{fmt_autolinks, fmt_class_method, fmt_classlike_decl, fmt_section},
+    traits::{ASTEntry, Access, BuildResult, EntityMethods, Entry, Include, NavItem, OutputEntry},
+};
+
+/// Where the documentation of a group comes from
+enum GroupDoc {
+    /// Plain markdown description from flash.toml
+    Config(String),
+    /// A whole doc comment with a @defgroup command
+    Comment(String),
+}
+
+/// The name of a group as used in URLs, like `ui-nodes` for `UI Nodes`
+fn slug(name: &str) -> String {
+    name.split(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_')))
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// A named collection of entities, specified with `@ingroup`, that doesn't
+/// have to follow namespaces
+pub struct Group<'e> {
+    name: String,
+    title: Option<String>,
+    doc: Option<GroupDoc>,
+    members: Vec<Entity<'e>>,
+    methods: Vec<Entity<'e>>,
+}
+
+impl<'e> Group<'e> {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            title: None,
+            doc: None,
+            members: Vec::new(),
+            methods: Vec::new(),
+        }
+    }
+
+    fn members_of(&self, kind: CppItemKind) -> Vec<&Entity<'e>> {
+        self.members
+            .iter()
+            .filter(|e| CppItemKind::from(e) == Some(kind))
+            .collect()
+    }
+
+    fn fmt_description(&self, builder: &'e Builder<'e>) -> Html {
+        match self.doc {
+            Some(GroupDoc::Config(ref desc)) => fmt_markdown(
+                builder,
+                &fmt_autolinks(
+                    builder,
+                    &fmt_doc_links(builder, desc, None, Some(format!("flash.toml group {}", self.name))),
+                ),
+                None::<fn(_) -> _>,
+            ),
+            Some(GroupDoc::Comment(ref raw)) => {
+                JSDocComment::parse(raw.clone(), builder).to_html(true)
+            }
+            None => Html::span(&["no-desc"], "No description provided"),
+        }
+    }
+}
+
+impl<'e> Entry<'e> for Group<'e> {
+    fn name(&self) -> String {
+        self.title.clone().unwrap_or(self.name.clone())
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::part("groups").join(UrlPath::part(&slug(&self.name)))
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("grid", false)), Vec::new())
+    }
+}
+
+impl<'e> OutputEntry<'e> for Group<'e> {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+        (
+            builder.config.templates.group.clone(),
+            vec![
+                ("name", HtmlText::new(self.name()).into()),
+                ("description", self.fmt_description(builder)),
+                (
+                    "classes",
+                    fmt_section(
+                        "Classes",
+                        self.members_of(CppItemKind::Class)
+                            .into_iter()
+                            .map(|cls| fmt_classlike_decl(cls, "class", builder))
+                            .collect(),
+                    ),
+                ),
+                (
+                    "structs",
+                    fmt_section(
+                        "Structs",
+                        self.members_of(CppItemKind::Struct)
+                            .into_iter()
+                            .map(|cls| fmt_classlike_decl(cls, "struct", builder))
+                            .collect(),
+                    ),
+                ),
+                (
+                    "functions",
+                    fmt_section(
+                        "Functions",
+                        self.members_of(CppItemKind::Function)
+                            .into_iter()
+                            .map(|fun| fmt_class_method(fun, builder))
+                            .collect(),
+                    ),
+                ),
+                (
+                    "member_functions",
+                    fmt_section(
+                        "Member functions",
+                        self.methods
+                            .iter()
+                            .map(|fun| fmt_class_method(fun, builder))
+                            .collect(),
+                    ),
+                ),
+            ],
+        )
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!(
+            "Documentation for the {} group in {}",
+            self.name(),
+            builder.config.project.name
+        )
+    }
+}
+
+/// All the groups in the project, from flash.toml, `@defgroup` comments and
+/// `@ingroup` commands
+#[derive(Default)]
+pub struct GroupList<'e> {
+    groups: BTreeMap<String, Group<'e>>,
+}
+
+impl<'e> GroupList<'e> {
+    fn group_mut(&mut self, name: &str) -> &mut Group<'e> {
+        self.groups
+            .entry(name.to_owned())
+            .or_insert_with(|| Group::new(name))
+    }
+
+    /// Collect every entity that could be a group member. Member functions
+    /// are marked as such since they don't have pages of their own
+    fn collect_entities(item: &CppItem<'e>, out: &mut Vec<(Entity<'e>, bool)>) {
+        match item {
+            CppItem::Namespace(ns) => {
+                for entry in ns.entries.values() {
+                    Self::collect_entities(entry, out);
+                }
+            }
+            CppItem::Class(_) | CppItem::Struct(_) => {
+                out.push((*item.entity(), false));
+                out.extend(
+                    item.entity()
+                        .get_member_functions(Access::All, Include::All)
+                        .into_iter()
+                        .map(|fun| (fun, true)),
+                );
+            }
+            CppItem::Function(_) => out.push((*item.entity(), false)),
+        }
+    }
+
+    /// Find standalone `/** ... */` blocks and runs of `///` or `//!` lines
+    /// that define groups. These aren't attached to any entity, so they have
+    /// to be read from the headers directly
+    fn find_defgroup_comments(path: &Path) -> Vec<String> {
+        let Ok(data) = fs::read_to_string(path) else {
+            return Vec::new();
+        };
+        let mut comments = Vec::new();
+        let mut current = Vec::new();
+        let mut in_block = false;
+        for line in data.lines() {
+            let content = line.trim_start();
+            if in_block {
+                current.push(line);
+                if content.contains("*/") {
+                    comments.push(current.join("\n"));
+                    current.clear();
+                    in_block = false;
+                }
+                continue;
+            }
+            if content.starts_with("///") || content.starts_with("//!") {
+                current.push(line);
+                continue;
+            }
+            if !current.is_empty() {
+                comments.push(current.join("\n"));
+                current.clear();
+            }
+            if let Some(after) = content.strip_prefix("/**") {
+                current.push(line);
+                if after.contains("*/") {
+                    comments.push(current.join("\n"));
+                    current.clear();
+                }
+                else {
+                    in_block = true;
+                }
+            }
+        }
+        if !current.is_empty() {
+            comments.push(current.join("\n"));
+        }
+        comments.retain(|raw| has_command(raw, "defgroup"));
+        comments
+    }
+
+    pub fn from_builder(builder: &Builder<'e>) -> Self {
+        let mut list = Self::default();
+
+        // Groups defined in flash.toml
+        for def in &builder.config.groups {
+            let group = list.group_mut(&def.name);
+            group.title = def.title.clone();
+            group.doc = def.description.clone().map(GroupDoc::Config);
+        }
+
+        let mut entities = Vec::new();
+        for item in builder.root.entries.values() {
+            Self::collect_entities(item, &mut entities);
+        }
+
+        // Groups defined through @defgroup in the headers
+        let files = entities
+            .iter()
+            .filter_map(|(e, _)| e.definition_file())
+            .chain(builder.config.all_includes())
+            .collect::<HashSet<_>>();
+        for file in files {
+            for raw in Self::find_defgroup_comments(&file) {
+                let comment = JSDocComment::parse(raw.clone(), builder);
+                let Some((name, title)) = comment.defgroup() else {
+                    continue;
+                };
+                let group = list.group_mut(name);
+                if !title.is_empty() {
+                    group.title = Some(title.clone());
+                }
+                group.doc = Some(GroupDoc::Comment(raw));
+            }
+        }
+
+        // Group members
+        for (entity, is_method) in entities {
+            let Some(comment) = JSDocComment::from_entity(&entity, builder) else {
+                continue;
+            };
+            for name in comment.groups() {
+                let group = list.group_mut(name);
+                if is_method {
+                    group.methods.push(entity);
+                }
+                else {
+                    group.members.push(entity);
+                }
+            }
+        }
+
+        list
+    }
+}
+
+impl<'e> Entry<'e> for GroupList<'e> {
+    fn name(&self) -> String {
+        String::from("Groups")
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::part("groups")
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        let mut handles = Vec::new();
+        for group in self.groups.values() {
+            handles.extend(group.build(builder)?);
+        }
+        Ok(handles)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_root(None, self.groups.values().map(|g| g.nav()).collect())
+    }
+}
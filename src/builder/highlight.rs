@@ -0,0 +1,347 @@
+use std::fs;
+
+use clang::{
+    token::{Token, TokenKind},
+    source::SourceRange,
+};
+
+use crate::html::{Html, HtmlElement, HtmlList, HtmlText};
+
+use super::{builder::Builder, doctest::write_temp_source};
+
+/// Rules for the built-in highlighter. These are nowhere near full grammars,
+/// but good enough to color config files and shell snippets in tutorials
+struct Grammar {
+    /// Line comment starters
+    comments: &'static [&'static str],
+    /// String delimiters
+    quotes: &'static [char],
+    keywords: &'static [&'static str],
+    /// Whether keywords are case-insensitive
+    ignore_case: bool,
+    booleans: &'static [&'static str],
+    /// Whether strings followed by `:` are object keys, like in JSON
+    string_keys: bool,
+    /// Whether words at the start of a line followed by `=` are keys, like
+    /// in TOML
+    line_keys: bool,
+    /// Whether `[tables]` at the start of a line are headers, like in TOML
+    tables: bool,
+    /// Whether the first word of a line is a command, like in shell scripts
+    line_commands: bool,
+    /// Whether `$NAME` and `${NAME}` are variables
+    variables: bool,
+}
+
+const JSON: Grammar = Grammar {
+    comments: &["//"],
+    quotes: &['"'],
+    keywords: &["null"],
+    ignore_case: false,
+    booleans: &["true", "false"],
+    string_keys: true,
+    line_keys: false,
+    tables: false,
+    line_commands: false,
+    variables: false,
+};
+
+const TOML: Grammar = Grammar {
+    comments: &["#"],
+    quotes: &['"', '\''],
+    keywords: &[],
+    ignore_case: false,
+    booleans: &["true", "false"],
+    string_keys: false,
+    line_keys: true,
+    tables: true,
+    line_commands: false,
+    variables: false,
+};
+
+const CMAKE: Grammar = Grammar {
+    comments: &["#"],
+    quotes: &['"'],
+    keywords: &[
+        "if", "elseif", "else", "endif", "foreach", "endforeach", "while", "endwhile",
+        "function", "endfunction", "macro", "endmacro", "return", "break", "continue",
+        "AND", "OR", "NOT", "PUBLIC", "PRIVATE", "INTERFACE", "STREQUAL", "MATCHES",
+        "DEFINED", "EXISTS", "CACHE", "PARENT_SCOPE",
+    ],
+    ignore_case: true,
+    booleans: &["ON", "OFF", "TRUE", "FALSE", "YES", "NO"],
+    string_keys: false,
+    line_keys: false,
+    tables: false,
+    line_commands: false,
+    variables: true,
+};
+
+const SHELL: Grammar = Grammar {
+    comments: &["#"],
+    quotes: &['"', '\''],
+    keywords: &[
+        "if", "then", "else", "elif", "fi", "for", "in", "do", "done", "while", "case",
+        "esac", "function", "export", "local", "return",
+    ],
+    ignore_case: false,
+    booleans: &["true", "false"],
+    string_keys: false,
+    line_keys: false,
+    tables: false,
+    line_commands: true,
+    variables: true,
+};
+
+/// A language that Flash can highlight at build time
+enum Language {
+    /// C and C++, which are tokenized with libclang
+    Cpp,
+    Grammar(&'static Grammar),
+}
+
+impl Language {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().as_str() {
+            "cpp" | "c++" | "cxx" | "hpp" | "c" | "h" => Language::Cpp,
+            "json" | "jsonc" => Language::Grammar(&JSON),
+            "toml" => Language::Grammar(&TOML),
+            "cmake" => Language::Grammar(&CMAKE),
+            "sh" | "bash" | "shell" | "zsh" | "console" => Language::Grammar(&SHELL),
+            _ => return None,
+        })
+    }
+}
+
+fn token(class: &str, text: &str) -> Html {
+    HtmlElement::new("span")
+        .with_class(class)
+        .with_text(text)
+        .into()
+}
+
+fn cpp_class(token: &Token, next: Option<&Token>, prev: Option<&Token>) -> Option<&'static str> {
+    Some(match token.get_kind() {
+        TokenKind::Comment => "comment",
+        TokenKind::Keyword => match token.get_spelling().as_str() {
+            "true" | "false" | "nullptr" => "boolean",
+            _ => "keyword",
+        },
+        TokenKind::Literal => {
+            if token.get_spelling().contains(['"', '\'']) {
+                "string"
+            }
+            else {
+                "number"
+            }
+        }
+        TokenKind::Punctuation => match token.get_spelling().as_str() {
+            "(" | ")" | "[" | "]" | "{" | "}" | ";" | "," | "." | "::" => "punctuation",
+            "#" => "macro",
+            _ => "operator",
+        },
+        TokenKind::Identifier => {
+            if prev.is_some_and(|p| p.get_spelling() == "#") {
+                "macro"
+            }
+            else if next.is_some_and(|n| n.get_spelling() == "(") {
+                "function"
+            }
+            else {
+                return None;
+            }
+        }
+    })
+}
+
+/// Highlight C++ using libclang's tokenizer. The code isn't compiled, so
+/// this works for snippets that don't compile on their own too
+fn highlight_cpp(builder: &Builder, code: &str) -> Result<Vec<Html>, String> {
+    let path = write_temp_source(builder, code)?;
+    let res = (|| {
+        let unit = builder
+            .index
+            .parser(&path)
+            .arguments(&["-x", "c++", "-nostdinc", "-nostdinc++"])
+            .skip_function_bodies(true)
+            .parse()
+            .map_err(|e| e.to_string())?;
+        let file = unit
+            .get_file(&path)
+            .ok_or(String::from("Unable to tokenize code"))?;
+        let tokens = SourceRange::new(
+            file.get_offset_location(0),
+            file.get_offset_location(code.len() as u32),
+        )
+        .tokenize();
+
+        let mut res = Vec::new();
+        let mut last = 0;
+        for (i, tok) in tokens.iter().enumerate() {
+            let range = tok.get_range();
+            let start = range.get_start().get_file_location().offset as usize;
+            let end = range.get_end().get_file_location().offset as usize;
+            if start < last || end > code.len() {
+                continue;
+            }
+            // Whitespace and anything clang skipped over is kept as-is
+            res.push(HtmlText::new(&code[last..start]).into());
+            let text = &code[start..end];
+            match cpp_class(tok, tokens.get(i + 1), i.checked_sub(1).and_then(|p| tokens.get(p))) {
+                Some(class) => res.push(token(class, text)),
+                None => res.push(HtmlText::new(text).into()),
+            }
+            last = end;
+        }
+        res.push(HtmlText::new(&code[last..]).into());
+        Ok(res)
+    })();
+
+    // We don't really care if we can remove the file or not
+    drop(fs::remove_file(path));
+
+    res
+}
+
+/// Length of a string starting with `quote` at the start of `code`,
+/// including escapes and the closing quote if there is one
+fn string_len(code: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in code.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        }
+        else if c == '\\' {
+            escaped = true;
+        }
+        else if c == quote {
+            return i + c.len_utf8();
+        }
+        // Unclosed strings end at the end of the line
+        else if c == '\n' {
+            return i;
+        }
+    }
+    code.len()
+}
+
+fn word_len(code: &str) -> usize {
+    code.find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')))
+        .unwrap_or(code.len())
+}
+
+/// Highlight code with one of the built-in grammars
+fn highlight_grammar(grammar: &Grammar, code: &str) -> Vec<Html> {
+    let mut res = Vec::new();
+    let mut text = String::new();
+    let mut line_start = true;
+    let mut i = 0;
+
+    let flush = |text: &mut String, res: &mut Vec<Html>| {
+        if !text.is_empty() {
+            res.push(HtmlText::new(std::mem::take(text)).into());
+        }
+    };
+
+    while let Some(c) = code[i..].chars().next() {
+        let rest = &code[i..];
+        let (class, len) = if c == '\n' {
+            line_start = true;
+            text.push(c);
+            i += 1;
+            continue;
+        }
+        else if c.is_whitespace() {
+            text.push(c);
+            i += c.len_utf8();
+            continue;
+        }
+        else if grammar.comments.iter().any(|s| rest.starts_with(s)) {
+            ("comment", rest.find('\n').unwrap_or(rest.len()))
+        }
+        else if grammar.quotes.contains(&c) {
+            let len = string_len(rest, c);
+            let is_key = grammar.string_keys && rest[len..].trim_start().starts_with(':');
+            (if is_key { "property" } else { "string" }, len)
+        }
+        else if grammar.tables && line_start && c == '[' {
+            ("class-name", rest.find(['\n', ']']).map(|p| p + 1).unwrap_or(rest.len()))
+        }
+        else if grammar.variables && c == '$' {
+            let len = if rest.starts_with("${") {
+                rest.find('}').map(|p| p + 1).unwrap_or(rest.len())
+            }
+            else {
+                1 + word_len(&rest[1..])
+            };
+            ("variable", len)
+        }
+        else if c.is_ascii_digit() || (c == '-' && rest[1..].starts_with(|c: char| c.is_ascii_digit())) {
+            ("number", 1 + word_len(&rest[1..]))
+        }
+        else if c.is_alphabetic() || c == '_' {
+            let len = word_len(rest);
+            let word = &rest[..len];
+            let after = rest[len..].trim_start_matches([' ', '\t']);
+            let matches = |list: &[&str]| {
+                list.iter().any(|w| if grammar.ignore_case { w.eq_ignore_ascii_case(word) } else { *w == word })
+            };
+            let class = if matches(grammar.booleans) {
+                "boolean"
+            }
+            else if matches(grammar.keywords) {
+                "keyword"
+            }
+            else if grammar.line_keys && line_start && after.starts_with(['=', '.']) {
+                "property"
+            }
+            // Commands at the start of shell lines and calls like in CMake
+            else if (grammar.line_commands && line_start) || after.starts_with('(') {
+                "function"
+            }
+            else {
+                text.push_str(word);
+                i += len;
+                line_start = false;
+                continue;
+            };
+            (class, len)
+        }
+        else {
+            let class = if "{}[](),;:.".contains(c) { "punctuation" } else { "operator" };
+            (class, c.len_utf8())
+        };
+
+        flush(&mut text, &mut res);
+        res.push(token(class, &rest[..len]));
+        line_start = false;
+        i += len;
+    }
+    flush(&mut text, &mut res);
+    res
+}
+
+/// Highlight a code block at build time so pages don't need to wait for
+/// Prism. Returns `None` for languages Flash doesn't know, which are left
+/// for Prism to highlight in the browser
+pub fn highlight(builder: &Builder, code: &str, lang: &str) -> Option<Html> {
+    let spans = match Language::from_name(lang)? {
+        Language::Cpp => highlight_cpp(builder, code)
+            .inspect_err(|e| builder.config.diagnostics.warn(
+                "highlight",
+                None,
+                format!("Unable to highlight code: {e}"),
+            ))
+            .ok()?,
+        Language::Grammar(grammar) => highlight_grammar(grammar, code),
+    };
+    Some(
+        HtmlElement::new("pre")
+            .with_child(
+                HtmlElement::new("code")
+                    .with_classes(&["highlighted", &format!("lang-{}", lang.to_lowercase())])
+                    .with_child(HtmlList::new(spans)),
+            )
+            .into(),
+    )
+}
@@ -0,0 +1,201 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::{
+    html::{Html, HtmlElement, HtmlText},
+    url::UrlPath,
+};
+
+use super::{
+    builder::Builder,
+    namespace::CppItemKind,
+    traits::{BuildResult, EntityMethods, Entry, NavItem, OutputEntry},
+};
+
+fn kind_icon(kind: &CppItemKind) -> (&'static str, bool) {
+    match kind {
+        CppItemKind::Namespace => ("layers", false),
+        CppItemKind::Class => ("box", false),
+        CppItemKind::Struct => ("box", true),
+        CppItemKind::Function => ("code", true),
+    }
+}
+
+fn index_letter(name: &str) -> char {
+    match name.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => c.to_ascii_uppercase(),
+        _ => '#',
+    }
+}
+
+/// A single entity listed under a name in the symbol index
+struct IndexItem {
+    kind: CppItemKind,
+    scope: String,
+    url: UrlPath,
+}
+
+/// Index entries by letter, then by (lowercase name, name)
+type IndexLetters = BTreeMap<char, BTreeMap<(String, String), Vec<IndexItem>>>;
+
+/// An A-Z index of every documented entity by its unqualified name, like
+/// the index at the back of a book
+pub struct SymbolIndex;
+
+impl SymbolIndex {
+    /// Collect all entities grouped first by index letter and then by name.
+    /// Entities with the same name in different scopes end up in the same
+    /// group
+    fn collect<'e>(builder: &'e Builder<'e>) -> IndexLetters {
+        let mut letters = IndexLetters::new();
+        // Namespaces don't have pages of their own, so they aren't listed
+        let entries = builder.root.get(&|entry| {
+            !matches!(CppItemKind::from(entry.entity()), Some(CppItemKind::Namespace))
+        });
+        for entry in entries {
+            let Some(kind) = CppItemKind::from(entry.entity()) else {
+                continue;
+            };
+            let Some(name) = entry.entity().get_name() else {
+                continue;
+            };
+            let mut scope = entry.entity().full_name();
+            scope.pop();
+
+            letters
+                .entry(index_letter(&name))
+                .or_default()
+                // Sort case-insensitively but keep differently cased names apart
+                .entry((name.to_lowercase(), name))
+                .or_default()
+                .push(IndexItem {
+                    kind,
+                    scope: scope.join("::"),
+                    url: entry.url(),
+                });
+        }
+        for groups in letters.values_mut() {
+            for items in groups.values_mut() {
+                items.sort_by(|a, b| a.scope.cmp(&b.scope));
+            }
+        }
+        letters
+    }
+
+    fn fmt_item(name: Option<&str>, item: &IndexItem, builder: &Builder) -> Html {
+        let (icon, variant) = kind_icon(&item.kind);
+        let url = item.url.to_absolute(builder.config.clone());
+        HtmlElement::new("a")
+            .with_class("index-item")
+            .with_attr("href", url.clone())
+            .with_attr("onclick", format!("return navigate('{url}')"))
+            .with_child(
+                HtmlElement::new("i")
+                    .with_attr("data-feather", icon)
+                    .with_class("icon")
+                    .with_class_opt(variant.then_some("variant")),
+            )
+            .with_child_opt(name.map(|name| Html::span(&["name"], name)))
+            .with_child(Html::span(
+                &["scope", "namespace"],
+                if item.scope.is_empty() { "(global)" } else { &item.scope },
+            ))
+            .into()
+    }
+
+    fn fmt_group(name: &str, items: &[IndexItem], builder: &Builder) -> Html {
+        // A name that is only used once doesn't need a group
+        if let [item] = items {
+            return Self::fmt_item(Some(name), item, builder);
+        }
+        HtmlElement::new("div")
+            .with_class("index-group")
+            .with_child(Html::span(&["name"], name))
+            .with_child(
+                HtmlElement::new("div").with_children(
+                    items
+                        .iter()
+                        .map(|item| Self::fmt_item(None, item, builder))
+                        .collect(),
+                ),
+            )
+            .into()
+    }
+
+    fn fmt_letters(letters: &IndexLetters) -> Html {
+        HtmlElement::new("div")
+            .with_class("index-letters")
+            .with_children(
+                letters
+                    .keys()
+                    .map(|letter| {
+                        HtmlElement::new("a")
+                            .with_attr("href", format!("#index-{letter}"))
+                            .with_text(letter.to_string())
+                            .into()
+                    })
+                    .collect(),
+            )
+            .into()
+    }
+
+    fn fmt_groups(letters: &IndexLetters, builder: &Builder) -> Html {
+        HtmlElement::new("div")
+            .with_class("symbol-index")
+            .with_children(
+                letters
+                    .iter()
+                    .map(|(letter, groups)| {
+                        HtmlElement::new("section")
+                            .with_attr("id", format!("index-{letter}"))
+                            .with_child(
+                                HtmlElement::new("h2").with_text(letter.to_string()),
+                            )
+                            .with_children(
+                                groups
+                                    .iter()
+                                    .map(|((_, name), items)| Self::fmt_group(name, items, builder))
+                                    .collect(),
+                            )
+                            .into()
+                    })
+                    .collect(),
+            )
+            .into()
+    }
+}
+
+impl<'e> Entry<'e> for SymbolIndex {
+    fn name(&self) -> String {
+        String::from("Index")
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::part("symbols")
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("list", false)), Vec::new())
+    }
+}
+
+impl<'e> OutputEntry<'e> for SymbolIndex {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+        let letters = Self::collect(builder);
+        (
+            builder.config.templates.symbol_index.clone(),
+            vec![
+                ("title", HtmlText::new(self.name()).into()),
+                ("letters", Self::fmt_letters(&letters)),
+                ("content", Self::fmt_groups(&letters, builder)),
+            ],
+        )
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!("Index of all entities in {}", builder.config.project.name)
+    }
+}
@@ -1,12 +1,18 @@
 
 use super::builder::Builder;
+use super::comment::Example;
+use super::highlight::highlight;
+use super::resolve::fmt_doc_links;
 use super::shared::fmt_emoji;
+use super::snippet::expand_includes;
 use super::traits::Entry;
-use crate::html::{Html, HtmlElement, HtmlText};
+use crate::config::Config;
+use crate::html::{GenHtml, Html, HtmlElement, HtmlText};
 use crate::lookahead::{CreateCachedLookahead, CachedLookahead};
 use crate::url::UrlPath;
-use pulldown_cmark::{CowStr, Event, Tag, LinkType};
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Tag, LinkType};
 use serde::{Deserialize, Deserializer};
+use std::path::Path;
 
 #[derive(Clone, PartialEq, Default)]
 pub enum Style {
@@ -45,24 +51,24 @@ impl Metadata {
     }
 }
 
-fn parse_markdown_metadata(doc: &str) -> (&str, Option<Metadata>) {
+fn parse_markdown_metadata(doc: &str) -> (&str, Result<Option<Metadata>, String>) {
     // if the document has no metadata just parse it as markdown
     if !doc.trim_start().starts_with("---") {
-        return (doc, None);
+        return (doc, Ok(None));
     }
 
     let doc = doc.trim_start().strip_prefix("---").unwrap();
 
     // make sure metadata ends properly
     let Some(metadata_end) = doc.find("---") else {
-        return (doc, None);
+        return (doc, Ok(None));
     };
     let metadata_str = &doc[..metadata_end];
 
     // parse metadata
     (
         &doc[metadata_end + 3..],
-        serde_yaml::from_str(metadata_str).expect("Invalid metadata in markdown")
+        serde_yaml::from_str(metadata_str).map_err(|e| format!("Invalid metadata: {e}"))
     )
 }
 
@@ -125,6 +131,38 @@ impl<
             return Some(Event::End(Tag::BlockQuote));
         }
         let event = self.iter.next()?;
+
+        // Highlight code blocks at build time. Code blocks marked with 
+        // `cpp flash` are analyzed like examples so they can link to the docs
+        if let Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(ref info))) = event {
+            let lang = info.split([' ', ',']).next().unwrap_or_default().to_owned();
+            let analyze = is_analyzed_fence(info);
+            let mut code = String::new();
+            for event in self.iter.by_ref() {
+                match event {
+                    Event::Text(t) => code.push_str(&t),
+                    Event::End(Tag::CodeBlock(_)) => break,
+                    _ => {}
+                }
+            }
+            let html = if analyze {
+                Example::new(code, true, self.builder).to_html()
+            }
+            else {
+                // Languages Flash can't highlight are left for Prism
+                highlight(self.builder, &code, &lang).unwrap_or_else(|| {
+                    HtmlElement::new("pre")
+                        .with_child(
+                            HtmlElement::new("code")
+                                .with_class(&format!("language-{lang}"))
+                                .with_text(&code),
+                        )
+                        .into()
+                })
+            };
+            return Some(Event::Html(CowStr::Boxed(Box::from(html.gen_html()))));
+        }
+
         Some(match event {
             // Don't format emojis inside code blocks lol
             Event::Text(t) => if self.inside_code_block {
@@ -245,12 +283,22 @@ impl<
     }
 }
 
+/// Whether a code block's info string like `cpp flash` asks for it to be 
+/// analyzed
+fn is_analyzed_fence(info: &str) -> bool {
+    let mut words = info.split(|c: char| c.is_whitespace() || c == ',');
+    matches!(words.next(), Some("cpp" | "c++" | "cxx" | "hpp"))
+        && words.any(|w| w == "flash")
+}
+
 #[allow(clippy::ptr_arg)]
 pub fn fmt_markdown<F: Fn(UrlPath) -> Option<UrlPath>>(
     builder: &Builder, text: &str, url_fixer: Option<F>
 ) -> Html {
-    // skip metadata
+    // skip metadata (invalid metadata has already been reported when the 
+    // tutorial was loaded)
     let (text, meta) = parse_markdown_metadata(text);
+    let meta = meta.ok().flatten();
 
     // pulldown_cmark doesn't automatically generate header links for me, and I 
     // need those to be able to have docs links. Unfortunately the mechanism it 
@@ -277,8 +325,21 @@ pub fn fmt_markdown<F: Fn(UrlPath) -> Option<UrlPath>>(
 }
 
 #[allow(clippy::ptr_arg)]
-pub fn extract_metadata_from_md(text: &String, default_title: Option<String>) -> Option<Metadata> {
+pub fn extract_metadata_from_md(
+    text: &String,
+    default_title: Option<String>,
+    config: &Config,
+    file: &Path,
+) -> Option<Metadata> {
     let (text, metadata) = parse_markdown_metadata(text);
+    let metadata = metadata
+        .inspect_err(|e| config.diagnostics.error(
+            "tutorial-metadata",
+            Some(file.to_string_lossy().to_string()),
+            e.as_str(),
+        ))
+        .ok()
+        .flatten();
 
     // if the metadata provided the title, no need to parse the doc for it
     if metadata.is_some() && metadata.as_ref().unwrap().title.is_some() {
@@ -326,13 +387,15 @@ pub fn output_tutorial<'e, T: Entry<'e>>(
     content: &str,
     links: Html,
 ) -> Vec<(&'static str, Html)> {
+    let location = Some(format!("Tutorial {}", entry.url()));
+    let content = expand_includes(&builder.config, content, location.clone());
     vec![
         ("title", HtmlText::new(entry.name()).into()),
         (
             "content",
             fmt_markdown(
                 builder,
-                content,
+                &fmt_doc_links(builder, &content, None, location),
                 Some(|url: UrlPath| {
                     Some(url.remove_extension(".md"))
                 }),
@@ -1,11 +1,20 @@
 #[allow(clippy::module_inception)]
 pub mod builder;
+pub mod api;
 pub mod class;
 pub mod comment;
+pub mod coverage;
+pub mod deprecated;
+pub mod doctest;
 pub mod files;
 pub mod function;
+pub mod group;
+pub mod highlight;
+pub mod index;
 pub mod namespace;
+pub mod resolve;
 pub mod shared;
+pub mod snippet;
 pub mod struct_;
 pub mod tutorial;
 pub mod traits;
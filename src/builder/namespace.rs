@@ -12,6 +12,7 @@ use super::{
     struct_::Struct,
 };
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum CppItemKind {
     Namespace,
     Class,
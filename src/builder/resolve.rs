@@ -0,0 +1,382 @@
+use std::sync::Arc;
+
+use clang::{Entity, EntityKind};
+
+use crate::{annotation::code_span_len, config::Config, url::UrlPath};
+
+use super::{
+    builder::Builder,
+    namespace::{CppItem, Namespace},
+    shared::member_fun_link,
+    traits::{ASTEntry, Access, EntityMethods, Include},
+};
+
+/// Something a reference in a doc comment may point to
+pub enum Target<'e> {
+    /// A namespace, class, struct or function with its own page
+    Entity(Entity<'e>),
+    /// A member function on a class page
+    Member(Entity<'e>, Entity<'e>),
+    /// A tutorial page
+    Tutorial(UrlPath),
+    /// An external URL
+    Url(String),
+}
+
+impl<'e> Target<'e> {
+    /// Where the docs of an entity are. Member functions and fields are
+    /// documented on their class page
+    pub fn of(entity: &Entity<'e>) -> Option<Self> {
+        match entity.get_kind() {
+            EntityKind::Method => Some(Target::Member(entity.get_semantic_parent()?, *entity)),
+            EntityKind::FieldDecl => Some(Target::Entity(entity.get_semantic_parent()?)),
+            _ => Some(Target::Entity(*entity)),
+        }
+    }
+
+    pub fn href(&self, config: Arc<Config>) -> Option<String> {
+        match self {
+            Target::Entity(entity) => entity.abs_docs_url(config).map(|url| url.to_string()),
+            Target::Member(class, member) => Some(format!(
+                "{}#{}",
+                class.abs_docs_url(config)?,
+                member_fun_link(member)?
+            )),
+            Target::Tutorial(url) => Some(url.to_absolute(config).to_string()),
+            Target::Url(url) => Some(url.clone()),
+        }
+    }
+
+    pub fn is_external(&self) -> bool {
+        matches!(self, Target::Url(_))
+    }
+}
+
+fn find_in_item<'e>(item: &CppItem<'e>, path: &[&str]) -> Option<Target<'e>> {
+    let Some((first, rest)) = path.split_first() else {
+        return Some(Target::Entity(*item.entity()));
+    };
+    match item {
+        CppItem::Namespace(ns) => find_in_namespace(ns, path),
+        CppItem::Class(_) | CppItem::Struct(_) if rest.is_empty() => item
+            .entity()
+            .get_member_functions(Access::All, Include::All)
+            .into_iter()
+            .find(|fun| fun.get_name().as_deref() == Some(*first))
+            .map(|fun| Target::Member(*item.entity(), fun)),
+        _ => None,
+    }
+}
+
+/// Find an entity in a namespace by its path relative to the namespace
+pub fn find_in_namespace<'e>(ns: &Namespace<'e>, path: &[&str]) -> Option<Target<'e>> {
+    let (first, rest) = path.split_first()?;
+    // Entries are keyed by display names, which for templates include the
+    // template arguments, so compare to the plain name instead
+    ns.entries
+        .values()
+        .filter(|item| item.entity().get_name().as_deref() == Some(*first))
+        .find_map(|item| find_in_item(item, rest))
+}
+
+/// Find an entity by its path relative to one of the classes `class` inherits
+/// from, searching the bases of bases too
+fn find_in_bases<'e>(builder: &Builder<'e>, class: &Entity<'e>, path: &[&str]) -> Option<Target<'e>> {
+    class.get_base_classes().into_iter().find_map(|base| {
+        let base_name = base.full_name();
+        let full_path = base_name
+            .iter()
+            .map(|s| s.as_str())
+            .chain(path.iter().copied())
+            .collect::<Vec<_>>();
+        find_in_namespace(&builder.root, &full_path)
+            .or_else(|| find_in_bases(builder, &base, path))
+    })
+}
+
+/// Every path `target` may refer to from a scope like `["geode", "Mod"]`,
+/// from the innermost scope outwards, along with how many parts of the scope
+/// each one starts with. Targets starting with `::` are only looked up from
+/// the global scope
+fn lookup_paths<'a>(target: &'a str, scope: &'a [String]) -> Vec<(usize, Vec<&'a str>)> {
+    let path = target
+        .trim_start_matches("::")
+        .split("::")
+        .collect::<Vec<_>>();
+    let depth = if target.starts_with("::") { 0 } else { scope.len() };
+    (0..=depth)
+        .rev()
+        .map(|depth| {
+            let full_path = scope[..depth]
+                .iter()
+                .map(|s| s.as_str())
+                .chain(path.iter().copied())
+                .collect();
+            (depth, full_path)
+        })
+        .collect()
+}
+
+/// Resolve a reference like `geode::Mod`, `Mod::get`, a tutorial path, or a
+/// plain URL. Names are first looked up relative to `scope` and then each of
+/// its enclosing scopes, so a comment in `geode::Mod` can refer to `get` or
+/// `Loader::get` without qualifying them. In class scopes, members inherited
+/// from base classes are found too
+pub fn resolve<'e>(builder: &Builder<'e>, target: &str, scope: Option<&Entity<'e>>) -> Option<Target<'e>> {
+    if target.starts_with("http://") || target.starts_with("https://") {
+        return Some(Target::Url(target.to_owned()));
+    }
+
+    let ancestors = scope.map(|s| s.ancestorage()).unwrap_or_default();
+    let scope = ancestors
+        .iter()
+        .map(|a| a.get_name().unwrap_or("_anon".into()))
+        .collect::<Vec<_>>();
+    for (depth, full_path) in lookup_paths(target, &scope) {
+        if let Some(found) = find_in_namespace(&builder.root, &full_path) {
+            return Some(found);
+        }
+        if depth > 0
+            && let Some(found) = find_in_bases(builder, &ancestors[depth - 1], &full_path[depth..])
+        {
+            return Some(found);
+        }
+    }
+
+    let url = UrlPath::parse(target).ok()?.remove_extension(".md");
+    builder.tutorials.has_tutorial(&url).then_some(Target::Tutorial(url))
+}
+
+/// Length of the fenced code block at the start of `text`, if there is one
+fn fenced_block_len(text: &str) -> Option<usize> {
+    let fence = text.trim_start_matches([' ', '\t']);
+    let fence = ["```", "~~~"].into_iter().find(|f| fence.starts_with(f))?;
+    let body_start = text.find('\n').map(|i| i + 1).unwrap_or(text.len());
+    let mut end = body_start;
+    for line in text[body_start..].split_inclusive('\n') {
+        end += line.len();
+        if line.trim_start().starts_with(fence) {
+            break;
+        }
+    }
+    Some(end)
+}
+
+fn is_link_path(target: &str) -> bool {
+    target.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == ':')
+        && target.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '#' | '~'))
+}
+
+/// Turn code spans like `` `Mod::get` `` into links if they name something
+/// that can be resolved. Unlike explicit links, code spans that don't resolve
+/// are left as-is without an error
+pub fn link_code_spans<'e>(builder: &Builder<'e>, text: &str, scope: Option<&Entity<'e>>) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut i = 0;
+    while let Some(start) = text[i..].find('`').map(|s| s + i) {
+        let len = code_span_len(&text[start..]);
+        let inner = text[start..start + len].trim_matches('`');
+        res.push_str(&text[i..start]);
+        // Code spans in brackets are already links
+        let href = (is_link_path(inner) && !text[..start].ends_with('['))
+            .then(|| resolve(builder, &inner.replace('#', "::"), scope))
+            .flatten()
+            .and_then(|t| t.href(builder.config.clone()));
+        match href {
+            Some(href) => res.push_str(&format!("[`{inner}`]({href})")),
+            None => res.push_str(&text[start..start + len]),
+        }
+        i = start + len;
+    }
+    res.push_str(&text[i..]);
+    res
+}
+
+/// Replace explicit links like ``[`Mod::get`]`` and `{@link geode::Mod#get}`
+/// in markdown with regular links to the docs. Links are resolved relative to
+/// `scope`, and links that can't be resolved are reported as errors with
+/// `location`
+pub fn fmt_doc_links<'e>(
+    builder: &Builder<'e>,
+    text: &str,
+    scope: Option<&Entity<'e>>,
+    location: Option<String>,
+) -> String {
+    let resolve_link = |target: &str| -> Option<String> {
+        let href = resolve(builder, &target.replace('#', "::"), scope)
+            .and_then(|t| t.href(builder.config.clone()));
+        if href.is_none() {
+            builder.config.diagnostics.error(
+                "unresolved-link",
+                location.clone(),
+                format!("Unable to resolve link to `{target}`"),
+            );
+        }
+        href
+    };
+    replace_doc_links(text, resolve_link)
+}
+
+/// Replace explicit links in markdown with the URLs `resolve_link` finds for
+/// them, or with just their label if it doesn't find one
+fn replace_doc_links<F: FnMut(&str) -> Option<String>>(text: &str, mut resolve_link: F) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        let rest = &text[i..];
+
+        // Leave code blocks alone
+        if (i == 0 || text[..i].ends_with('\n')) && let Some(len) = fenced_block_len(rest) {
+            res.push_str(&rest[..len]);
+            i += len;
+            continue;
+        }
+
+        // [`target`], unless it's actually a regular markdown link or a 
+        // reference to a link definition
+        if let Some(inner) = rest.strip_prefix("[`") && let Some(end) = inner.find("`]") {
+            let target = &inner[..end];
+            let after = &inner[end + 2..];
+            if is_link_path(target)
+                && !after.starts_with(['(', '[', ':'])
+                && !text.contains(&format!("[`{target}`]:"))
+            {
+                if let Some(href) = resolve_link(target) {
+                    res.push_str(&format!("[`{target}`]({href})"));
+                }
+                else {
+                    res.push_str(&format!("`{target}`"));
+                }
+                i += end + 4;
+                continue;
+            }
+        }
+
+        // {@link target} or {@link target label}
+        if let Some(inner) = rest.strip_prefix("{@link")
+            && inner.starts_with(char::is_whitespace)
+            && let Some(end) = inner.find('}')
+        {
+            let link = inner[..end].trim();
+            let (target, label) = link
+                .split_once(char::is_whitespace)
+                .map(|(target, label)| (target, Some(label.trim())))
+                .unwrap_or((link, None));
+            let label = label.map(|l| l.to_owned()).unwrap_or(format!("`{target}`"));
+            if let Some(href) = resolve_link(target) {
+                res.push_str(&format!("[{label}]({href})"));
+            }
+            else {
+                res.push_str(&label);
+            }
+            i += "{@link".len() + end + 1;
+            continue;
+        }
+
+        // Leave code spans alone
+        if c == '`' {
+            let len = code_span_len(rest);
+            res.push_str(&rest[..len]);
+            i += len;
+            continue;
+        }
+
+        res.push(c);
+        i += c.len_utf8();
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Resolve `Mod` and `Mod::get` like the docs for a project would
+    fn replace(text: &str) -> String {
+        replace_doc_links(text, |target| {
+            let path = target.replace('#', "::");
+            ["Mod", "Mod::get"]
+                .contains(&path.as_str())
+                .then(|| format!("/docs/{}", path.replace("::", "/")))
+        })
+    }
+
+    #[test]
+    fn lookup_from_scope() {
+        let scope = ["geode".to_owned(), "Mod".to_owned()];
+        assert_eq!(
+            lookup_paths("Loader::get", &scope),
+            vec![
+                (2, vec!["geode", "Mod", "Loader", "get"]),
+                (1, vec!["geode", "Loader", "get"]),
+                (0, vec!["Loader", "get"]),
+            ],
+        );
+        assert_eq!(lookup_paths("get", &[]), vec![(0, vec!["get"])]);
+    }
+
+    #[test]
+    fn lookup_from_global_scope() {
+        let scope = ["geode".to_owned(), "Mod".to_owned()];
+        assert_eq!(lookup_paths("::Mod", &scope), vec![(0, vec!["Mod"])]);
+    }
+
+    #[test]
+    fn code_span_lengths() {
+        assert_eq!(code_span_len("`Mod` rest"), 5);
+        assert_eq!(code_span_len("`` a ` b `` rest"), 11);
+        assert_eq!(code_span_len("``` unclosed"), 3);
+    }
+
+    #[test]
+    fn fenced_block_lengths() {
+        assert_eq!(fenced_block_len("```cpp\ncode\n```\nafter"), Some(16));
+        assert_eq!(fenced_block_len("~~~\nunclosed"), Some(12));
+        assert_eq!(fenced_block_len("not a block"), None);
+    }
+
+    #[test]
+    fn link_paths() {
+        assert!(is_link_path("Mod::get"));
+        assert!(is_link_path("::geode::Mod#get"));
+        assert!(is_link_path("Mod::~Mod"));
+        assert!(!is_link_path("~Mod"));
+        assert!(!is_link_path("1up"));
+        assert!(!is_link_path("a b"));
+    }
+
+    #[test]
+    fn bracket_links() {
+        assert_eq!(replace("See [`Mod::get`]."), "See [`Mod::get`](/docs/Mod/get).");
+        assert_eq!(replace("See [`Nope`]."), "See `Nope`.");
+    }
+
+    #[test]
+    fn markdown_links_are_left_alone() {
+        for text in [
+            "[`Mod`](https://example.org)",
+            "[`Mod`][mod]",
+            "[`Mod`]\n\n[`Mod`]: https://example.org",
+        ] {
+            assert_eq!(replace(text), text);
+        }
+    }
+
+    #[test]
+    fn at_links() {
+        assert_eq!(replace("{@link Mod#get}"), "[`Mod#get`](/docs/Mod/get)");
+        assert_eq!(replace("{@link Mod::get the getter}"), "[the getter](/docs/Mod/get)");
+        assert_eq!(replace("{@link Nope the label}"), "the label");
+        assert_eq!(replace("{@linkplain Mod}"), "{@linkplain Mod}");
+    }
+
+    #[test]
+    fn code_is_left_alone() {
+        for text in [
+            "```\n[`Mod`] {@link Mod}\n```\n",
+            "Write `{@link Mod}` for links",
+        ] {
+            assert_eq!(replace(text), text);
+        }
+    }
+}
@@ -8,6 +8,7 @@ use crate::html::{Html, HtmlElement, HtmlList, HtmlText};
 use clang::{Accessibility, Entity, EntityKind, Type, TypeKind};
 use multipeek::{IteratorExt, MultiPeek};
 use pulldown_cmark::CowStr;
+use std::collections::HashMap;
 use std::str::Chars;
 use std::sync::Arc;
 
@@ -205,18 +206,42 @@ pub fn fmt_field(field: &Entity, builder: &Builder) -> Html {
         )
         .with_child(
             HtmlElement::new("div").with_child(
-                field
-                    .get_comment()
-                    .map(|s| JSDocComment::parse(s, builder).to_html(true))
+                JSDocComment::from_entity(field, builder)
+                    .map(|c| c.to_html(true))
                     .unwrap_or(Html::span(&["no-desc"], "No description provided")),
             ),
         )
         .into()
 }
 
+/// Badges shown next to an entity's name, like whether it's deprecated or
+/// what its thread safety is
+pub fn fmt_badges(entity: &Entity, builder: &Builder) -> Html {
+    HtmlList::new(
+        entity
+            .is_deprecated()
+            .then(|| Html::span(&["badge", "deprecated"], "deprecated"))
+            .into_iter()
+            .chain(
+                JSDocComment::from_entity(entity, builder)
+                    .and_then(|c| c.thread_tag())
+                    .map(|tag| {
+                        HtmlElement::new("span")
+                            .with_classes(&["badge", "thread"])
+                            .with_child(Html::feather("cpu"))
+                            .with_text(tag)
+                            .into()
+                    }),
+            )
+            .collect(),
+    )
+    .into()
+}
+
 fn fmt_fun_signature(fun: &Entity, builder: &Builder) -> Html {
     HtmlElement::new("summary")
         .with_classes(&["entity", "fun"])
+        .with_class_opt(fun.is_deprecated().then_some("deprecated"))
         .with_child_opt(fmt_template_args(fun, builder))
         .with_child(HtmlElement::new("span")
             .with_class("function-signature")
@@ -260,6 +285,7 @@ fn fmt_fun_signature(fun: &Entity, builder: &Builder) -> Html {
                 )
             )
         )
+        .with_child(fmt_badges(fun, builder))
         .into()
 }
 
@@ -272,8 +298,9 @@ pub fn fmt_class_method(fun: &Entity, builder: &Builder) -> Html {
         )
         .with_child(
             HtmlElement::new("div").with_child(
-                fun.get_comment()
-                    .map(|s| JSDocComment::parse(s, builder).to_html(true))
+                JSDocComment::from_entity(fun, builder)
+                    .or_else(|| JSDocComment::inherited(fun, builder))
+                    .map(|c| c.to_html(true))
                     .unwrap_or(Html::span(&["no-desc"], "No description provided")),
             ),
         )
@@ -286,18 +313,20 @@ pub fn fmt_classlike_decl(class: &Entity, keyword: &str, builder: &Builder) -> H
         .with_child(
             HtmlElement::new("summary")
                 .with_classes(&["entity", keyword])
+                .with_class_opt(class.is_deprecated().then_some("deprecated"))
                 .with_child(Html::span(&["keyword", "space-after"], keyword))
                 .with_child(Html::span(
                     &["name"],
                     &class.get_name().unwrap_or("_anon".into()),
                 ))
                 .with_child_opt(fmt_template_args(class, builder))
-                .with_child(HtmlText::new(";")),
+                .with_child(HtmlText::new(";"))
+                .with_child(fmt_badges(class, builder)),
         )
         .with_child(
             HtmlElement::new("div").with_child(
-                class.get_comment()
-                    .map(|s| JSDocComment::parse(s, builder).to_html(true))
+                JSDocComment::from_entity(class, builder)
+                    .map(|c| c.to_html(true))
                     .unwrap_or(Html::span(&["no-desc"], "No description provided")),
             ),
         )
@@ -391,12 +420,11 @@ pub fn output_entity<'e, T: ASTEntry<'e>>(
 ) -> Vec<(&'static str, Html)> {
     vec![
         ("name", HtmlText::new(entry.name()).into()),
+        ("badges", fmt_badges(entry.entity(), builder)),
         (
             "description",
-            entry
-                .entity()
-                .get_comment()
-                .map(|s| JSDocComment::parse(s, builder).to_html(false))
+            JSDocComment::from_entity(entry.entity(), builder)
+                .map(|c| c.to_html(false))
                 .unwrap_or(Html::span(&["no-desc"], "No description provided")),
         ),
         (
@@ -407,12 +435,9 @@ pub fn output_entity<'e, T: ASTEntry<'e>>(
             "examples",
             fmt_section(
                 "Examples",
-                entry
-                    .entity()
-                    .get_comment()
-                    .map(|s| {
-                        JSDocComment::parse(s, builder)
-                            .examples()
+                JSDocComment::from_entity(entry.entity(), builder)
+                    .map(|c| {
+                        c.examples()
                             .iter()
                             .map(|example| example.to_html())
                             .collect()
@@ -514,36 +539,35 @@ pub fn output_function<'e, T: ASTEntry<'e>>(
     ent
 }
 
-fn fmt_autolinks_recursive(
+/// Collect the names of all entities that words in descriptions should be 
+/// autolinked to, along with their URLs
+pub fn collect_autolinks(
     entity: &CppItem,
     config: Arc<Config>,
-    annotations: &mut Annotations<'_>,
-    prefix: &Option<char>,
+    links: &mut HashMap<String, String>,
 ) {
-    annotations.rewind();
-    while let Some(word) = annotations.next() {
-        // skip stuff that have all-lowercase names (so words like "get" 
-        // and "data" don't get autolinked)
-        if !word.chars().all(|c| c.is_lowercase()) && *word == entity.name() {
-            if let Some(url) = entity.entity().abs_docs_url(config.clone()) {
-                annotations.annotate(format!("[{word}]({})", url));
-            }
-        }
+    let name = entity.name();
+    // skip stuff that have all-lowercase names (so words like "get" 
+    // and "data" don't get autolinked)
+    if !name.chars().all(|c| c.is_lowercase())
+        && let Some(url) = entity.entity().abs_docs_url(config.clone())
+    {
+        links.insert(name, url.to_string());
     }
 
     if let CppItem::Namespace(ns) = entity {
         for v in ns.entries.values() {
-            fmt_autolinks_recursive(v, config.clone(), annotations, prefix);
+            collect_autolinks(v, config.clone(), links);
         }
     }
 }
 
-pub fn fmt_autolinks(builder: &Builder, text: &str, prefix: Option<char>) -> String {
+pub fn fmt_autolinks(builder: &Builder, text: &str) -> String {
     let mut annotations = Annotations::new(text);
-    for entry in builder.root.entries.values() {
-        fmt_autolinks_recursive(
-            entry, builder.config.clone(), &mut annotations, &prefix
-        );
+    while let Some(word) = annotations.next() {
+        if let Some(url) = builder.autolinks.get(&word) {
+            annotations.annotate(format!("[{word}]({url})"));
+        }
     }
     annotations.into_result()
 }
@@ -0,0 +1,150 @@
+use std::{collections::HashMap, fs};
+
+use crate::config::Config;
+
+/// The name of the region a line like `// [setup]` marks, if it is a marker
+fn region_marker(line: &str) -> Option<&str> {
+    let name = line
+        .trim()
+        .strip_prefix("//")?
+        .trim()
+        .strip_prefix('[')?
+        .strip_suffix(']')?;
+    name.chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_'))
+        .then_some(name)
+}
+
+/// Remove the indentation shared by all the non-empty lines
+fn dedent(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or(line.trim_start()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The code between two `// [region]` marker lines in `content`, or all of
+/// it if there's no region. Markers of other regions are left out, but only
+/// if they come in pairs, so a lone comment like `// [deprecated]` is kept
+fn extract_region(content: &str, region: Option<&str>) -> Option<String> {
+    let mut marker_counts = HashMap::new();
+    for name in content.lines().filter_map(region_marker) {
+        *marker_counts.entry(name).or_insert(0) += 1;
+    }
+
+    let mut lines = Vec::new();
+    let mut found = false;
+    let mut inside = region.is_none();
+    for line in content.lines() {
+        let marker = region_marker(line).filter(|name| marker_counts[name] >= 2);
+        match marker {
+            Some(name) if Some(name) == region => {
+                found = true;
+                inside = !inside;
+            }
+            Some(_) => {}
+            None if inside => lines.push(line),
+            None => {}
+        }
+    }
+    (region.is_none() || found).then(|| dedent(&lines))
+}
+
+/// Read the code in `path` (relative to the project root) between two
+/// `// [region]` marker lines, or the whole file if there's no region
+pub fn read_snippet(config: &Config, path: &str, region: Option<&str>) -> Result<String, String> {
+    let content = fs::read_to_string(config.input_dir.join(path))
+        .map_err(|e| format!("Unable to read {path}: {e}"))?;
+    extract_region(&content, region).ok_or_else(|| {
+        format!("Region `{}` not found in {path}", region.unwrap_or_default())
+    })
+}
+
+/// Replace `{{#include path}}` and `{{#include path:region}}` directives in
+/// markdown with the code they point to. Files and regions that can't be
+/// found are reported as errors with `location`
+pub fn expand_includes(config: &Config, text: &str, location: Option<String>) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{#include") {
+        res.push_str(&rest[..start]);
+        let after = &rest[start + "{{#include".len()..];
+        let Some(end) = after.find("}}") else {
+            res.push_str(&rest[start..]);
+            return res;
+        };
+        let target = after[..end].trim();
+        let (path, region) = match target.rsplit_once(':') {
+            Some((path, region)) if !region.contains(['/', '\\']) => (path, Some(region)),
+            _ => (target, None),
+        };
+        match read_snippet(config, path, region) {
+            Ok(code) => res.push_str(&code),
+            Err(e) => config.diagnostics.error("include-file", location.clone(), e),
+        }
+        rest = &after[end + 2..];
+    }
+    res.push_str(rest);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+#include <Geode/Geode.hpp>
+
+// [setup]
+void setup() {
+    // [deprecated]
+    init();
+    // [hook]
+    hook();
+    // [hook]
+}
+// [setup]
+";
+
+    #[test]
+    fn region_markers() {
+        assert_eq!(region_marker("// [setup]"), Some("setup"));
+        assert_eq!(region_marker("    //[my-region_2]  "), Some("my-region_2"));
+        assert_eq!(region_marker("// [not a region]"), None);
+        assert_eq!(region_marker("call(); // [setup]"), None);
+        assert_eq!(region_marker("/* [setup] */"), None);
+    }
+
+    #[test]
+    fn whole_file_without_region() {
+        assert_eq!(
+            extract_region("int a;\n// [x]\nint b;\n// [x]\n", None).as_deref(),
+            Some("int a;\nint b;"),
+        );
+    }
+
+    #[test]
+    fn region_is_dedented_without_other_markers() {
+        assert_eq!(
+            extract_region(EXAMPLE, Some("hook")).as_deref(),
+            Some("hook();"),
+        );
+        assert_eq!(
+            extract_region(EXAMPLE, Some("setup")).as_deref(),
+            Some("void setup() {\n    // [deprecated]\n    init();\n    hook();\n}"),
+        );
+    }
+
+    #[test]
+    fn unpaired_marker_is_not_a_region() {
+        assert_eq!(extract_region(EXAMPLE, Some("deprecated")), None);
+        assert_eq!(extract_region(EXAMPLE, Some("missing")), None);
+    }
+}
@@ -37,7 +37,7 @@ impl<'e> Entry<'e> for Struct<'e> {
         NavItem::new_link(
             &self.name(), self.url(), Some(("box", true)),
             SubItem::for_classlike(&self.entity)
-        )
+        ).deprecated(self.entity.is_deprecated())
     }
 }
 
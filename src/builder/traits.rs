@@ -1,4 +1,4 @@
-use clang::{Entity, EntityKind, Accessibility};
+use clang::{Availability, Entity, EntityKind, Accessibility};
 
 use std::{path::PathBuf, sync::Arc, collections::HashMap};
 
@@ -10,7 +10,7 @@ use crate::{
     url::UrlPath,
 };
 
-use super::{namespace::CppItemKind, builder::Builder, shared::member_fun_link};
+use super::{namespace::CppItemKind, builder::Builder, comment::Deprecation, shared::member_fun_link};
 
 pub trait EntityMethods<'e> {
     /// Get the config source for this entity
@@ -37,12 +37,23 @@ pub trait EntityMethods<'e> {
     /// Get the fully qualified name for this entity
     fn full_name(&self) -> Vec<String>;
 
+    /// Get a `file:line:column` string for where this entity's doc comment
+    /// is, or where the entity itself is if it has no comment
+    fn comment_location(&self, config: Arc<Config>) -> Option<String>;
+
+    /// Check if this entity has a `[[deprecated]]` attribute or a 
+    /// `@deprecated` command in its doc comment
+    fn is_deprecated(&self) -> bool;
+
     /// Get the parents of this entity
     fn ancestorage(&self) -> Vec<Entity<'e>>;
 
     /// Gets all the member functions from this entity, assuming it is a class-like entity
     fn get_member_functions(&self, visibility: Access, include_statics: Include) -> Vec<Entity<'e>>;
 
+    /// Gets the classes this entity directly inherits from, assuming it is a class-like entity
+    fn get_base_classes(&self) -> Vec<Entity<'e>>;
+
     /// Gets the function arguments for this method, including templated ones
     fn get_function_arguments(&self) -> Option<Vec<Entity<'e>>>;
 
@@ -135,6 +146,26 @@ impl<'e> EntityMethods<'e> for Entity<'e> {
             .collect()
     }
 
+    fn comment_location(&self, config: Arc<Config>) -> Option<String> {
+        let loc = self
+            .get_comment_range()
+            .map(|range| range.get_start())
+            .or_else(|| self.get_location())?
+            .get_file_location();
+        let path = loc.file?.get_path();
+        Some(format!(
+            "{}:{}:{}",
+            path.strip_prefix(&config.input_dir).unwrap_or(&path).to_string_lossy(),
+            loc.line,
+            loc.column,
+        ))
+    }
+
+    fn is_deprecated(&self) -> bool {
+        self.get_availability() == Availability::Deprecated
+            || self.get_comment().is_some_and(|c| Deprecation::from_comment(&c).is_some())
+    }
+
     fn ancestorage(&self) -> Vec<Entity<'e>> {
         let mut ancestors = Vec::new();
         if let Some(parent) = self.get_semantic_parent() {
@@ -181,6 +212,16 @@ impl<'e> EntityMethods<'e> for Entity<'e> {
             .collect()
     }
 
+    fn get_base_classes(&self) -> Vec<Entity<'e>> {
+        self
+            .get_children()
+            .into_iter()
+            .filter(|child| child.get_kind() == EntityKind::BaseSpecifier)
+            .filter_map(|base| base.get_type()?.get_declaration())
+            .map(|base| base.get_definition().unwrap_or(base))
+            .collect()
+    }
+
     fn get_function_arguments(&self) -> Option<Vec<Entity<'e>>> {
         if !matches!(self.get_kind(), EntityKind::FunctionTemplate | EntityKind::FunctionDecl | EntityKind::Method) {
             return None;
@@ -248,7 +289,7 @@ impl SubItem {
 pub enum NavItem {
     Root(Option<String>, Vec<NavItem>),
     Dir(String, Vec<NavItem>, Option<(String, bool)>, bool),
-    Link(String, UrlPath, Option<(String, bool)>, Vec<SubItem>),
+    Link(String, UrlPath, Option<(String, bool)>, Vec<SubItem>, bool),
 }
 
 impl NavItem {
@@ -258,7 +299,17 @@ impl NavItem {
         icon: Option<(&str, bool)>,
         suboptions: Vec<SubItem>,
     ) -> NavItem {
-        NavItem::Link(name.into(), url, icon.map(|s| (s.0.into(), s.1)), suboptions)
+        NavItem::Link(name.into(), url, icon.map(|s| (s.0.into(), s.1)), suboptions, false)
+    }
+
+    /// Mark a link as pointing to something deprecated
+    pub fn deprecated(self, deprecated: bool) -> NavItem {
+        match self {
+            NavItem::Link(name, url, icon, suboptions, _) => {
+                NavItem::Link(name, url, icon, suboptions, deprecated)
+            }
+            other => other,
+        }
     }
 
     pub fn new_dir(name: &str, items: Vec<NavItem>, icon: Option<(&str, bool)>) -> NavItem {
@@ -280,7 +331,7 @@ impl NavItem {
 
     pub fn suboptions_titles(&self, config: Arc<Config>) -> HashMap<String, usize> {
         match self {
-            NavItem::Link(name, _, _, suboptions) => {
+            NavItem::Link(name, _, _, suboptions, _) => {
                 let mut res = HashMap::new();
                 for opt in suboptions.iter().map(|o| format!("{}::{}", name, o.title)) {
                     if let Some(r) = res.get_mut(&opt) {
@@ -306,7 +357,7 @@ impl NavItem {
 
     pub fn to_html(&self, config: Arc<Config>) -> Html {
         match self {
-            NavItem::Link(name, url, icon, _) => {
+            NavItem::Link(name, url, icon, _, deprecated) => {
                 HtmlList::new(vec![
                     HtmlElement::new("a")
                         .with_attr(
@@ -314,6 +365,7 @@ impl NavItem {
                             format!("return navigate('{}')", url.to_absolute(config.clone())),
                         )
                         .with_attr("href", url.to_absolute(config.clone()))
+                        .with_class_opt(deprecated.then_some("deprecated"))
                         .with_child_opt(icon.as_ref().map(|i| {
                             HtmlElement::new("i")
                                 .with_attr("data-feather", &i.0)
@@ -321,6 +373,9 @@ impl NavItem {
                                 .with_class_opt(i.1.then_some("variant"))
                         }))
                         .with_child(HtmlText::new(name))
+                        .with_child_opt(
+                            deprecated.then(|| Html::span(&["badge", "deprecated"], "deprecated")),
+                        )
                         .into()
                 ]).into()
             }
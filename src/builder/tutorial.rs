@@ -19,23 +19,29 @@ pub struct Tutorial {
 }
 
 impl Tutorial {
-    pub fn new(config: Arc<Config>, path: UrlPath) -> Self {
-        let unparsed_content = fs::read_to_string(
-            config
-                .input_dir
-                .join(&config.tutorials.as_ref().unwrap().dir)
-                .join(path.to_pathbuf()),
-        )
-        .unwrap_or_else(|_| panic!("Unable to read tutorial {}", path.to_raw_string()));
+    pub fn new(config: Arc<Config>, path: UrlPath) -> Option<Self> {
+        let file = config
+            .input_dir
+            .join(&config.tutorials.as_ref().unwrap().dir)
+            .join(path.to_pathbuf());
+        let unparsed_content = fs::read_to_string(&file)
+            .inspect_err(|e| config.diagnostics.error(
+                "tutorial-read",
+                Some(file.to_string_lossy().to_string()),
+                format!("Unable to read tutorial: {e}"),
+            ))
+            .ok()?;
 
-        Self {
+        Some(Self {
             metadata: extract_metadata_from_md(
                 &unparsed_content,
-                path.remove_extension(".md").raw_file_name()
+                path.remove_extension(".md").raw_file_name(),
+                &config,
+                &file,
             ).unwrap_or_default(),
             unparsed_content,
             path,
-        }
+        })
     }
 }
 
@@ -135,8 +141,7 @@ impl TutorialFolder {
                     .to_path_buf();
 
                 let Ok(url) = UrlPath::try_from(&stripped_path) else { continue; };
-                println!("creating tutorial for {}", url);
-                let tut = Tutorial::new(config.clone(), url);
+                let Some(tut) = Tutorial::new(config.clone(), url) else { continue; };
                 tutorials.insert(tut.name(), tut);
             }
         }
@@ -152,7 +157,9 @@ impl TutorialFolder {
             is_root: false,
             is_open: depth < 2,
             path: UrlPath::try_from(&stripped_path).ok()?,
-            metadata: index.as_ref().and_then(|i| extract_metadata_from_md(i, None)),
+            metadata: index.as_ref().and_then(|i| {
+                extract_metadata_from_md(i, None, &config, &path.join("index.md"))
+            }),
             index,
             folders,
             tutorials,
@@ -194,6 +201,30 @@ impl TutorialFolder {
         vec.into_iter().map(|(_, v)| v).collect()
     }
 
+    /// Check if a tutorial or tutorial folder with this URL exists
+    pub fn has_tutorial(&self, url: &UrlPath) -> bool {
+        (!self.is_root && self.path == *url)
+            || self.tutorials.values().any(|tut| tut.url() == *url)
+            || self.folders.values().any(|dir| dir.has_tutorial(url))
+    }
+
+    /// The markdown source of every tutorial and folder index in this
+    /// folder and its subfolders, with paths relative to the tutorials
+    /// directory
+    pub fn sources(&self) -> Vec<(UrlPath, String)> {
+        let mut res = Vec::new();
+        if let Some(ref index) = self.index {
+            res.push((self.path.join(UrlPath::part("index.md")), index.clone()));
+        }
+        for tut in self.tutorials.values() {
+            res.push((tut.path.clone(), tut.unparsed_content.clone()));
+        }
+        for folder in self.folders.values() {
+            res.extend(folder.sources());
+        }
+        res
+    }
+
     pub fn tutorials_sorted(&self) -> Vec<&Tutorial> {
         let mut vec = self.tutorials.iter().collect::<Vec<_>>();
         vec.sort_unstable_by(|a, b| {
@@ -1,9 +1,9 @@
 use flash_macros::decl_config;
 use glob::glob;
 use serde::{Deserialize, Deserializer};
-use std::{fs, path::PathBuf, sync::Arc};
+use std::{collections::HashMap, fs, path::{Path, PathBuf}, sync::Arc};
 
-use crate::url::UrlPath;
+use crate::{builder::api::ApiModel, diagnostics::{Diagnostics, OutputFormat}, url::UrlPath, versions::version_dir};
 
 fn parse_template<'de, D>(deserializer: D) -> Result<Arc<String>, D::Error>
 where
@@ -19,24 +19,42 @@ fn parse_sources<'de, D>(deserializer: D) -> Result<Vec<Arc<Source>>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    Ok(Vec::<RawSource>::deserialize(deserializer)?
+    Vec::<RawSource>::deserialize(deserializer)?
         .into_iter()
-        .map(|src| Arc::from(Source::from_raw(src).unwrap()))
-        .collect())
+        .map(|src| Source::from_raw(src).map(Arc::from).map_err(serde::de::Error::custom))
+        .collect()
 }
 
 fn parse_glob<'de, D>(deserializer: D) -> Result<Vec<PathBuf>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    Ok(Vec::<PathBuf>::deserialize(deserializer)?
-        .iter()
-        .flat_map(|src| {
-            glob(src.to_str().unwrap())
-                .unwrap_or_else(|_| panic!("Invalid glob pattern {}", src.to_str().unwrap()))
-                .map(|g| g.unwrap())
-        })
-        .collect())
+    let mut res = Vec::new();
+    for src in Vec::<PathBuf>::deserialize(deserializer)? {
+        res.extend(expand_glob(&src).map_err(serde::de::Error::custom)?);
+    }
+    Ok(res)
+}
+
+/// Split a base URL like `https://docs.example.org/v3` into its origin and
+/// path. Base URLs that are only a path have no origin
+fn split_base_url(url: &str) -> Result<(Option<String>, &str), String> {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return Ok((None, url));
+    };
+    let host_end = rest.find('/').unwrap_or(rest.len());
+    if scheme.is_empty() || host_end == 0 {
+        return Err(format!("Invalid base URL {url}"));
+    }
+    Ok((Some(format!("{scheme}://{}", &rest[..host_end])), &rest[host_end..]))
+}
+
+fn expand_glob(pattern: &Path) -> Result<Vec<PathBuf>, String> {
+    let pattern = pattern.to_string_lossy();
+    glob(&pattern)
+        .map_err(|e| format!("Invalid glob pattern {pattern}: {e}"))?
+        .map(|g| g.map_err(|e| format!("Unable to read {}: {}", e.path().display(), e.error())))
+        .collect()
 }
 
 macro_rules! default_template {
@@ -72,28 +90,19 @@ pub struct Source {
 
 impl Source {
     pub fn from_raw(src: RawSource) -> Result<Source, String> {
-        let exclude = src
-            .exclude
-            .into_iter()
-            .map(|p| src.dir.to_pathbuf().join(p))
-            .flat_map(|src| {
-                glob(src.to_str().unwrap())
-                    .unwrap_or_else(|_| panic!("Invalid glob pattern {}", src.to_str().unwrap()))
-                    .map(|g| g.unwrap())
-            })
-            .collect::<Vec<_>>();
-
-        let include = src
-            .include
-            .into_iter()
-            .map(|p| src.dir.to_pathbuf().join(p))
-            .flat_map(|src| {
-                glob(src.to_str().unwrap())
-                    .unwrap_or_else(|_| panic!("Invalid glob pattern {}", src.to_str().unwrap()))
-                    .map(|g| g.unwrap())
-            })
-            .filter(|p| !exclude.contains(p))
-            .collect::<Vec<_>>();
+        let mut exclude = Vec::new();
+        for p in src.exclude {
+            exclude.extend(expand_glob(&src.dir.to_pathbuf().join(p))?);
+        }
+
+        let mut include = Vec::new();
+        for p in src.include {
+            include.extend(
+                expand_glob(&src.dir.to_pathbuf().join(p))?
+                    .into_iter()
+                    .filter(|p| !exclude.contains(p)),
+            );
+        }
 
         Ok(Self {
             name: src.name,
@@ -104,12 +113,59 @@ impl Source {
     }
 }
 
+/// What to do when libclang reports errors while parsing the headers
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnErrors {
+    /// Report the errors and build the docs anyway
+    Warn,
+    /// Stop before any docs are built
+    Fail,
+}
+
+/// Lowest severity of libclang diagnostics that gets reported
+#[derive(Deserialize, Clone, Copy, PartialEq, PartialOrd)]
+#[serde(rename_all = "lowercase")]
+pub enum ClangSeverity {
+    Note,
+    Warning,
+    Error,
+}
+
+/// How a project-defined comment command is rendered
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandKind {
+    /// A titled section like the one for parameters
+    Section,
+    /// A short tag next to the version
+    Tag,
+    /// A callout box like the ones for notes and warnings
+    Callout,
+    /// A titled bullet list with one item per use of the command
+    List,
+}
+
 decl_config! {
     struct Script {
         name: String,
         content: Arc<String> as parse_template,
     }
 
+    struct GroupDef {
+        name: String,
+        title?: String,
+        description?: String,
+    }
+
+    struct CommandDef {
+        title: String,
+        kind: CommandKind = CommandKind::Section,
+        param: bool = false,
+        icon?: String,
+        class?: String,
+    }
+
     struct RawSource {
         name: String,
         dir: UrlPath,
@@ -125,17 +181,24 @@ decl_config! {
             repository?: String,
             tree?: String,
             icon?: PathBuf,
+            base_url?: String,
         },
         tutorials? {
             dir: PathBuf,
             assets: Vec<PathBuf> as parse_glob = Vec::new(),
         },
         sources: Vec<Arc<Source>> as parse_sources,
+        groups: Vec<GroupDef> = Vec::new(),
         run? {
             prebuild: Vec<String> = Vec::new(),
         },
         analysis {
             compile_args: Vec<String> = Vec::new(),
+            on_errors: OnErrors = OnErrors::Warn,
+            min_severity: ClangSeverity = ClangSeverity::Error,
+        },
+        comments {
+            commands: HashMap<String, CommandDef> = HashMap::new(),
         },
         cmake? {
             config_args: Vec<String> = Vec::new(),
@@ -154,6 +217,11 @@ decl_config! {
             page:           Arc<String> as parse_template = default_template!("../templates/page.html"),
             tutorial:       Arc<String> as parse_template = default_template!("../templates/tutorial.html"),
             tutorial_index: Arc<String> as parse_template = default_template!("../templates/tutorial-index.html"),
+            symbol_index:   Arc<String> as parse_template = default_template!("../templates/symbol-index.html"),
+            group:          Arc<String> as parse_template = default_template!("../templates/group.html"),
+            coverage:       Arc<String> as parse_template = default_template!("../templates/coverage.html"),
+            deprecated:     Arc<String> as parse_template = default_template!("../templates/deprecated.html"),
+            changes:        Arc<String> as parse_template = default_template!("../templates/changes.html"),
         },
         scripts {
             css: Vec<Script> = default_scripts!("default.css", "nav.css", "content.css", "themes.css"),
@@ -162,14 +230,47 @@ decl_config! {
         let input_dir: PathBuf,
         let output_dir: PathBuf,
         let output_url: Option<UrlPath>,
+        let output_origin: Option<String>,
+        let output_root: PathBuf,
+        let root_url: Option<UrlPath>,
+        let version_dir: Option<String>,
+        let previous_api: Option<ApiModel>,
+        let cli: CliOptions,
+        let diagnostics: Diagnostics,
     }
 }
 
+/// Options passed on the command line rather than through flash.toml
+#[derive(Default)]
+pub struct CliOptions {
+    /// Generate a documentation coverage report
+    pub coverage: bool,
+    /// Fail the build if documentation coverage is below this percentage
+    pub min_coverage: Option<f64>,
+    /// Fail the build if there are any warnings
+    pub deny_warnings: bool,
+    /// Compile all examples and fail the build if they have errors
+    pub check_examples: bool,
+    /// Don't load anything from CDNs in the generated docs
+    pub offline: bool,
+    /// Make links relative so the docs can be opened without a web server
+    pub relative_links: bool,
+    /// The URL the docs are hosted at, overriding `project.base-url`
+    pub base_url: Option<String>,
+    /// Build the docs into a subdirectory for the project's version
+    pub versioned: bool,
+    /// Output directory of an earlier build to list API changes since
+    pub diff_against: Option<PathBuf>,
+    /// How to print diagnostics
+    pub format: OutputFormat,
+}
+
 impl Config {
     pub fn parse(
         input_dir: PathBuf,
         output_dir: PathBuf,
         output_url: Option<UrlPath>,
+        cli: CliOptions,
     ) -> Result<Arc<Config>, String> {
         let mut config: Config = toml::from_str(
             &fs::read_to_string(input_dir.join("flash.toml"))
@@ -180,9 +281,38 @@ impl Config {
         config.input_dir = input_dir;
         config.output_dir = output_dir;
         config.output_url = output_url;
+        config.cli = cli;
+
+        if let Some(base) = config.cli.base_url.clone().or(config.project.base_url.clone()) {
+            let (origin, path) = split_base_url(&base)?;
+            config.output_origin = origin;
+            config.output_url = Some(UrlPath::parse(path)?);
+        }
+
+        config.output_root = config.output_dir.clone();
+        config.root_url = config.output_url.clone();
+        if config.cli.versioned {
+            let dir = version_dir(&config.project.version);
+            config.output_dir = config.output_dir.join(&dir);
+            config.output_url = Some(
+                config.output_url.clone().unwrap_or(UrlPath::new()).join(UrlPath::part(&dir))
+            );
+            config.version_dir = Some(dir);
+        }
+
+        // Read the old API now, since it may be in the output directory that
+        // is about to be replaced
+        if let Some(ref old) = config.cli.diff_against {
+            config.previous_api = Some(ApiModel::load(old)?);
+        }
         Ok(Arc::from(config))
     }
 
+    /// The full URL of a page, if the origin the docs are hosted at is known
+    pub fn canonical_url(&self, page: &UrlPath) -> Option<String> {
+        self.output_origin.as_ref().map(|origin| format!("{origin}{page}/"))
+    }
+
     pub fn all_includes(&self) -> Vec<PathBuf> {
         self.sources
             .iter()
@@ -190,3 +320,32 @@ impl Config {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_url_with_origin() {
+        assert_eq!(
+            split_base_url("https://docs.example.org/v3"),
+            Ok((Some("https://docs.example.org".into()), "/v3")),
+        );
+        assert_eq!(
+            split_base_url("http://localhost:8080"),
+            Ok((Some("http://localhost:8080".into()), "")),
+        );
+    }
+
+    #[test]
+    fn base_url_without_origin() {
+        assert_eq!(split_base_url("/docs/v3"), Ok((None, "/docs/v3")));
+        assert_eq!(split_base_url(""), Ok((None, "")));
+    }
+
+    #[test]
+    fn invalid_base_url() {
+        assert!(split_base_url("https:///docs").is_err());
+        assert!(split_base_url("://example.org").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use std::{collections::HashSet, fmt::Display, sync::Mutex};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// How diagnostics are printed once the build is done
+#[derive(Clone, Copy, Default, ValueEnum, Debug)]
+pub enum OutputFormat {
+    /// Human-readable lines with a summary
+    #[default]
+    Text,
+    /// A single JSON object for tools and CI
+    Json,
+}
+
+/// A problem found while building the docs
+#[derive(Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stable identifier for the kind of problem, like `unresolved-link`
+    pub code: &'static str,
+    /// Where the problem is, usually as `file:line:column`
+    pub location: Option<String>,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}[{}]: ", self.severity, self.code)?;
+        if let Some(ref location) = self.location {
+            write!(f, "{location}: ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Serialize)]
+struct Summary<'a> {
+    errors: usize,
+    warnings: usize,
+    /// Percentage of documented entities, if `--coverage` was passed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    coverage: Option<f64>,
+    diagnostics: &'a [Diagnostic],
+}
+
+/// Collects diagnostics during the build. The same comment may be formatted
+/// many times, so each distinct diagnostic is only kept once
+#[derive(Default)]
+pub struct Diagnostics {
+    seen: Mutex<HashSet<Diagnostic>>,
+    list: Mutex<Vec<Diagnostic>>,
+    coverage: Mutex<Option<f64>>,
+}
+
+impl Diagnostics {
+    pub fn report(&self, diagnostic: Diagnostic) {
+        if self.seen.lock().unwrap().insert(diagnostic.clone()) {
+            self.list.lock().unwrap().push(diagnostic);
+        }
+    }
+
+    pub fn warn<S: Into<String>>(&self, code: &'static str, location: Option<String>, message: S) {
+        self.report(Diagnostic {
+            severity: Severity::Warning,
+            code,
+            location,
+            message: message.into(),
+        });
+    }
+
+    pub fn error<S: Into<String>>(&self, code: &'static str, location: Option<String>, message: S) {
+        self.report(Diagnostic {
+            severity: Severity::Error,
+            code,
+            location,
+            message: message.into(),
+        });
+    }
+
+    /// Include the documentation coverage in the summary
+    pub fn set_coverage(&self, percent: f64) {
+        *self.coverage.lock().unwrap() = Some(percent);
+    }
+
+    fn count_in(list: &[Diagnostic], severity: Severity) -> usize {
+        list.iter().filter(|d| d.severity == severity).count()
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        Self::count_in(&self.list.lock().unwrap(), severity)
+    }
+
+    pub fn print(&self, format: OutputFormat) {
+        let list = self.list.lock().unwrap();
+        let errors = Self::count_in(&list, Severity::Error);
+        let warnings = Self::count_in(&list, Severity::Warning);
+        let coverage = *self.coverage.lock().unwrap();
+        match format {
+            OutputFormat::Text => {
+                for diagnostic in list.iter() {
+                    println!("{diagnostic}");
+                }
+                if !list.is_empty() {
+                    println!("{errors} errors, {warnings} warnings");
+                }
+                if let Some(coverage) = coverage {
+                    println!("Documentation coverage: {coverage:.1}%");
+                }
+            }
+            OutputFormat::Json => println!(
+                "{}",
+                serde_json::to_string(&Summary {
+                    errors,
+                    warnings,
+                    coverage,
+                    diagnostics: &list,
+                })
+                .unwrap()
+            ),
+        }
+    }
+
+    /// Fail if there were any errors, or any warnings if those are denied
+    pub fn check(&self, deny_warnings: bool) -> Result<(), String> {
+        let errors = self.count(Severity::Error);
+        let warnings = self.count(Severity::Warning);
+        if errors > 0 {
+            Err(format!("Docs build failed with {errors} errors"))
+        }
+        else if deny_warnings && warnings > 0 {
+            Err(format!("Docs build failed with {warnings} warnings (--deny-warnings)"))
+        }
+        else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use crate::diagnostics::Diagnostics;
+
+/// The Feather icons Flash uses itself, so offline docs don't need to load
+/// feather-icons from a CDN
+static FEATHER_ICONS: LazyLock<HashMap<String, String>> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("../../templates/feather-icons.json"))
+        .expect("Built-in icons should be valid JSON")
+});
+
+/// The SVG for a Feather icon, the same as `feather.replace()` would create
+pub fn feather_svg(name: &str, class: Option<&str>) -> Option<String> {
+    let content = FEATHER_ICONS.get(name)?;
+    Some(format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"24\" height=\"24\" \
+        viewBox=\"0 0 24 24\" fill=\"none\" stroke=\"currentColor\" stroke-width=\"2\" \
+        stroke-linecap=\"round\" stroke-linejoin=\"round\" class=\"feather feather-{name}{}\">\
+        {content}</svg>",
+        class.map(|c| format!(" {c}")).unwrap_or_default(),
+    ))
+}
+
+fn attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!(" {name}=\""))? + name.len() + 3;
+    let end = tag[start..].find('"')?;
+    Some(&tag[start..start + end])
+}
+
+/// Replace every `<i data-feather="name"></i>` in a page with the icon's SVG.
+/// Icons Flash doesn't know are left as-is and reported
+pub fn inline_icons(html: &str, diagnostics: &Diagnostics) -> String {
+    let mut res = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find("<i ") {
+        res.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let Some(end) = tail.find("</i>").map(|e| e + "</i>".len()) else {
+            break;
+        };
+        let elem = &tail[..end];
+        let tag = &elem[..elem.find('>').unwrap_or(elem.len())];
+        match attr(tag, "data-feather") {
+            Some(name) => match feather_svg(name, attr(tag, "class")) {
+                Some(svg) => res.push_str(&svg),
+                None => {
+                    diagnostics.warn(
+                        "offline-icon",
+                        None,
+                        format!("Icon `{name}` isn't available in offline mode"),
+                    );
+                    res.push_str(elem);
+                }
+            },
+            None => res.push_str(elem),
+        }
+        rest = &tail[end..];
+    }
+    res.push_str(rest);
+    res
+}
+
+/// Script that makes the built-in icons available to `script.js`
+pub fn icons_script() -> String {
+    format!(
+        "<script>const FLASH_ICONS = {};</script>",
+        serde_json::to_string(&*FEATHER_ICONS).unwrap_or(String::from("{}"))
+    )
+}
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use crate::{config::Config, url::UrlPath};
+
+/// Extensions of files that links point to directly. Links to anything else
+/// are pages, which are directories with an `index.html`
+const FILE_EXTENSIONS: &[&str] = &[
+    "css", "js", "json", "html", "txt", "ico", "png", "jpg", "jpeg", "gif", "svg", "webp",
+    "avif", "mp4", "webm", "pdf", "zip", "woff", "woff2", "ttf",
+];
+
+fn is_file(path: &UrlPath) -> bool {
+    path.raw_file_name()
+        .and_then(|name| name.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()))
+        .is_some_and(|ext| FILE_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Make a link relative to the page at `page`, if it points inside the docs
+fn relative_link(link: &str, page: &UrlPath, config: &Arc<Config>) -> Option<String> {
+    if !link.starts_with('/') || link.starts_with("//") {
+        return None;
+    }
+    let split = link.find(['#', '?']).unwrap_or(link.len());
+    let (path, rest) = link.split_at(split);
+    let path = UrlPath::parse(path).ok()?;
+    let root = config.output_url.clone().unwrap_or(UrlPath::new());
+    if !path.starts_with(&root) {
+        return None;
+    }
+    let path = path.strip_prefix(&root);
+    let mut res = path.relative_to(page);
+    if !is_file(&path) {
+        res += "/index.html";
+    }
+    Some(res + rest)
+}
+
+/// Rewrite every `href` and `src` in a page that points inside the docs to be
+/// relative to the page, and to point to the page's `index.html` so it can be
+/// opened without a web server
+pub fn relative_links(html: &str, page: &UrlPath, config: &Arc<Config>) -> String {
+    let mut res = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = [" href=\"", " src=\""]
+        .into_iter()
+        .filter_map(|attr| rest.find(attr).map(|i| i + attr.len()))
+        .min()
+    {
+        res.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find('"').unwrap_or(rest.len());
+        let link = &rest[..end];
+        match relative_link(link, page, config) {
+            Some(rel) => res.push_str(&rel),
+            None => res.push_str(link),
+        }
+        rest = &rest[end..];
+    }
+    res.push_str(rest);
+    res
+}
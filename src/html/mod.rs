@@ -1,5 +1,8 @@
 use std::collections::HashMap;
 
+pub mod icons;
+pub mod links;
+pub mod offline;
 pub mod process;
 
 pub trait GenHtml: Into<Html> {
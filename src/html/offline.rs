@@ -0,0 +1,42 @@
+use std::{fs, path::Path};
+
+use crate::{config::Config, url::UrlPath};
+
+use super::icons::icons_script;
+
+macro_rules! offline_assets {
+    ($($name: expr),* $(,)?) => {
+        &[$(($name, include_bytes!(concat!("../../templates/offline/", $name)))),*]
+    };
+}
+
+/// Files built into Flash that offline docs use in place of ones loaded from
+/// CDNs. They are written to `offline/` in the output directory
+const OFFLINE_ASSETS: &[(&str, &[u8])] = offline_assets!(
+    "fonts.css",
+    "open-sans-regular.woff2",
+    "open-sans-italic.woff2",
+    "open-sans-700.woff2",
+    "source-code-pro-regular.woff2",
+    "OPEN-SANS-LICENSE.txt",
+    "SOURCE-CODE-PRO-LICENSE.txt",
+);
+
+/// Write the built-in offline assets to the output directory
+pub fn write_offline_assets(output_dir: &Path) -> Result<(), String> {
+    let dir = output_dir.join("offline");
+    fs::create_dir_all(&dir).map_err(|e| format!("Unable to create offline assets directory: {e}"))?;
+    for (name, content) in OFFLINE_ASSETS {
+        fs::write(dir.join(name), content).map_err(|e| format!("Unable to save {name}: {e}"))?;
+    }
+    Ok(())
+}
+
+/// What offline docs load in place of the CDN links in `cdn.html`
+pub fn offline_head(config: &Config) -> String {
+    format!(
+        "{}<link rel=\"stylesheet\" href=\"{}/offline/fonts.css\">",
+        icons_script(),
+        config.output_url.as_ref().unwrap_or(&UrlPath::new()),
+    )
+}
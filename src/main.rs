@@ -2,80 +2,124 @@
 #![feature(iter_advance_by)]
 #![feature(iter_intersperse)]
 
-use crate::{analyze::create_docs, url::UrlPath, normalize::Normalize};
-use clap::Parser;
-use config::Config;
-use std::{fs, path::{PathBuf, Path}, process::exit, io, time::Instant};
+use crate::{analyze::create_docs, builder::api::diff_builds, url::UrlPath, normalize::Normalize, versions::update_versions};
+use clap::{Parser, Subcommand};
+use config::{CliOptions, Config};
+use diagnostics::OutputFormat;
+use std::{fs, path::PathBuf, process::exit, time::Instant};
 
 mod analyze;
 mod builder;
 mod cmake;
 mod config;
+mod diagnostics;
 mod html;
 mod url;
 mod normalize;
 mod annotation;
 mod lookahead;
+mod versions;
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compare the public APIs of two docs builds and list what was added,
+    /// removed and changed
+    Diff {
+        /// Output directory of the older build, or its api.json
+        old: PathBuf,
+
+        /// Output directory of the newer build, or its api.json
+        new: PathBuf,
+
+        /// How to print the changes
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+}
 
 #[derive(Parser, Debug)]
-#[command(name("Flash"), version, about)]
+#[command(name("Flash"), version, about, args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Input directory with the flash.json file
-    #[arg(short, long)]
-    input: PathBuf,
+    #[arg(short, long, required = true)]
+    input: Option<PathBuf>,
 
     /// Output directory where to place the generated docs
-    #[arg(short, long)]
-    output: PathBuf,
+    #[arg(short, long, required = true)]
+    output: Option<PathBuf>,
 
     /// Whether to overwrite output directory if it already exists
     #[arg(long, default_value_t = false)]
     overwrite: bool,
-}
 
-fn remove_dir_contents<P: AsRef<Path>>(path: P) -> io::Result<()> {
-    for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let path = entry.path();
+    /// Generate a report of how much of the public API is documented
+    #[arg(long, default_value_t = false)]
+    coverage: bool,
 
-        if entry.file_type()?.is_dir() {
-            remove_dir_contents(&path)?;
-            fs::remove_dir(path)?;
-        } else {
-            fs::remove_file(path)?;
-        }
-    }
-    Ok(())
+    /// Fail if less than this percentage of the public API is documented.
+    /// Implies --coverage
+    #[arg(long)]
+    min_coverage: Option<f64>,
+
+    /// Fail if there are any warnings
+    #[arg(long, default_value_t = false)]
+    deny_warnings: bool,
+
+    /// Compile every example in doc comments and C++ code block in
+    /// tutorials, and fail if any of them have errors
+    #[arg(long, default_value_t = false)]
+    check_examples: bool,
+
+    /// Build docs that don't load anything from CDNs, for hosting on
+    /// networks without internet access. Flash's own icons are inlined and
+    /// the fonts are copied to the output, while Prism and Twemoji are left out
+    #[arg(long, default_value_t = false)]
+    offline: bool,
+
+    /// Make every link relative to the page it's on and use full page loads
+    /// instead of fetching pages, so the docs work when opened from disk
+    #[arg(long, default_value_t = false)]
+    relative_links: bool,
+
+    /// The URL the docs will be hosted at, like `/docs/v3` or
+    /// `https://docs.example.org/`. Defaults to `project.base-url`, or the
+    /// output directory if it's a relative path
+    #[arg(long)]
+    base_url: Option<String>,
+
+    /// Build the docs into a subdirectory for the project version, like
+    /// `v3.2`, and add it to the `versions.json` of the output directory.
+    /// Docs for other versions are left as-is
+    #[arg(long, default_value_t = false)]
+    versioned: bool,
+
+    /// Output directory of an earlier build. Adds a page listing the API
+    /// changes since that build
+    #[arg(long)]
+    diff_against: Option<PathBuf>,
+
+    /// How to print warnings and errors
+    #[arg(long, value_enum, default_value = "text")]
+    format: OutputFormat,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), String> {
     let args = Args::parse();
 
-    // Check if output dir exists
-    if args.output.exists()
-        // Check if it's empty
-        && args.output.read_dir().map(|mut i| i.next().is_some()).unwrap_or(false)
-        // Then overwrite must be specified
-        && !args.overwrite
-    {
-        println!(
-            "Output directory {} already exists and no --overwrite option was specified, aborting",
-            args.output.to_str().unwrap()
-        );
-        exit(1);
-    }
-
-    // Clear output dir if it exists
-    if args.output.exists() {
-        remove_dir_contents(&args.output).unwrap();
-    }
-    else {
-        fs::create_dir_all(&args.output).unwrap();
+    if let Some(Command::Diff { old, new, format }) = args.command {
+        return diff_builds(&old, &new, format);
     }
+    // Clap makes sure these are set if there's no subcommand
+    let (Some(input), Some(output)) = (args.input, args.output) else {
+        unreachable!()
+    };
 
-    let relative_output = if args.output.is_relative() {
-        Some(UrlPath::try_from(&args.output).ok()).flatten()
+    let relative_output = if output.is_relative() {
+        Some(UrlPath::try_from(&output).ok()).flatten()
     } else {
         None
     };
@@ -83,32 +127,84 @@ async fn main() -> Result<(), String> {
     // Relink working directory to input dir and use absolute path for output
     // Not using fs::canonicalize because that returns UNC paths on Windows and
     // those break things
-    let full_output = if args.output.is_absolute() {
-        args.output
+    let full_output = if output.is_absolute() {
+        output
     } else {
-        std::env::current_dir().unwrap().join(args.output).normalize()
+        std::env::current_dir().unwrap().join(output).normalize()
     };
-    let full_input = if args.input.is_absolute() {
-        args.input
+    let full_input = if input.is_absolute() {
+        input
     } else {
-        std::env::current_dir().unwrap().join(args.input).normalize()
+        std::env::current_dir().unwrap().join(input).normalize()
     };
+    let diff_against = args.diff_against.map(|old| {
+        if old.is_absolute() {
+            old
+        } else {
+            std::env::current_dir().unwrap().join(old).normalize()
+        }
+    });
     std::env::set_current_dir(&full_input).expect(
         "Unable to set input dir as working directory \
             (probable reason is it doesn't exist)",
     );
 
     // Parse config
-    let conf = Config::parse(full_input, full_output, relative_output)?;
+    let cli = CliOptions {
+        coverage: args.coverage || args.min_coverage.is_some(),
+        min_coverage: args.min_coverage,
+        deny_warnings: args.deny_warnings,
+        check_examples: args.check_examples,
+        offline: args.offline,
+        relative_links: args.relative_links,
+        base_url: args.base_url,
+        versioned: args.versioned,
+        diff_against,
+        format: args.format,
+    };
+    let conf = Config::parse(full_input, full_output, relative_output, cli)?;
+
+    // Check if output dir exists. Versioned docs only replace the docs for
+    // their own version. The old docs are only removed once the headers have
+    // been parsed successfully
+    let output = &conf.output_dir;
+    if output.exists()
+        // Check if it's empty
+        && output.read_dir().map(|mut i| i.next().is_some()).unwrap_or(false)
+        // Then overwrite must be specified
+        && !args.overwrite
+    {
+        println!(
+            "Output directory {} already exists and no --overwrite option was specified, aborting",
+            output.to_str().unwrap()
+        );
+        exit(1);
+    }
+
+    if !output.exists() {
+        fs::create_dir_all(output).unwrap();
+    }
 
     // Build the docs
-    println!(
-        "Building docs for {} ({})",
-        conf.project.name, conf.project.version
-    );
+    let text_output = matches!(conf.cli.format, OutputFormat::Text);
+    if text_output {
+        println!(
+            "Building docs for {} ({})",
+            conf.project.name, conf.project.version
+        );
+    }
     let now = Instant::now();
-    create_docs(conf.clone()).await?;
-    println!("Docs built for {} in {}s", conf.project.name, now.elapsed().as_secs());
+    let res = create_docs(conf.clone()).await;
+
+    // Report problems even if the build failed
+    conf.diagnostics.print(conf.cli.format);
+    res?;
+    conf.diagnostics.check(conf.cli.deny_warnings)?;
+    update_versions(&conf)?;
+
+    if text_output {
+        println!("Docs built for {} in {}s", conf.project.name, now.elapsed().as_secs());
+    }
 
     Ok(())
 }
@@ -161,6 +161,22 @@ impl UrlPath {
     pub fn is_empty(&self) -> bool {
         self.parts.is_empty()
     }
+
+    /// Get this path relative to the directory `base`, like `../other/page`
+    pub fn relative_to<T: AsRef<UrlPath>>(&self, base: T) -> String {
+        let base = &base.as_ref().parts;
+        let common = self
+            .parts
+            .iter()
+            .zip(base)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let res = std::iter::repeat_n(String::from(".."), base.len() - common)
+            .chain(self.url_safe_parts().into_iter().skip(common))
+            .collect::<Vec<_>>()
+            .join("/");
+        if res.is_empty() { String::from(".") } else { res }
+    }
 }
 
 struct UrlVisitor;
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, fs, path::{Path, PathBuf}};
+
+use crate::{config::Config, url::UrlPath};
+
+/// A version listed in `versions.json`
+#[derive(Serialize, Deserialize, Clone)]
+struct VersionEntry {
+    /// The directory the version's docs are in, like `v3.2`
+    name: String,
+    /// The full version the docs were last built for, like `3.2.1`
+    version: String,
+    /// The root URL of the version's docs
+    url: String,
+}
+
+/// The `versions.json` manifest at the output root, which lists every
+/// published version of the docs
+#[derive(Serialize, Deserialize, Default)]
+struct VersionManifest {
+    latest: Option<String>,
+    versions: Vec<VersionEntry>,
+}
+
+/// The directory docs for a version are placed in, like `v3.2` for `3.2.1`.
+/// Versions that don't start with a number, like `nightly`, are used as-is
+pub fn version_dir(version: &str) -> String {
+    let version = version.trim().trim_start_matches('v');
+    let parts = version
+        .split('.')
+        .take_while(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+        .take(2)
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        version.replace(['/', '\\', ' '], "-")
+    }
+    else {
+        format!("v{}", parts.join("."))
+    }
+}
+
+fn numeric_parts(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split(['.', '-'])
+        .map_while(|p| p.parse().ok())
+        .collect()
+}
+
+/// Order versions by their numbers, so `10.0` comes after `2.0`
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    numeric_parts(a).cmp(&numeric_parts(b)).then_with(|| a.cmp(b))
+}
+
+/// Find every page under `dir` as paths relative to `root`
+fn find_pages(root: &Path, dir: &Path, pages: &mut Vec<PathBuf>) -> Result<(), String> {
+    for entry in fs::read_dir(dir).map_err(|e| format!("Unable to read {}: {e}", dir.display()))? {
+        let path = entry.map_err(|e| format!("Unable to read {}: {e}", dir.display()))?.path();
+        if path.is_dir() {
+            find_pages(root, &path, pages)?;
+        }
+        else if path.file_name().is_some_and(|n| n == "index.html") {
+            pages.push(path.parent().unwrap().strip_prefix(root).unwrap().to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// Replace the `latest` directory with pages that redirect to the same page in
+/// the version `name`
+fn write_latest_alias(config: &Config, name: &str) -> Result<(), String> {
+    let latest = config.output_root.join("latest");
+    if latest.exists() {
+        fs::remove_dir_all(&latest)
+            .map_err(|e| format!("Unable to remove old latest docs: {e}"))?;
+    }
+
+    let mut pages = Vec::new();
+    find_pages(&config.output_dir, &config.output_dir, &mut pages)?;
+    let root = config.root_url.clone().unwrap_or(UrlPath::new());
+    for page in pages {
+        let path = UrlPath::try_from(&page)?;
+        let target = UrlPath::part(name).join(&path);
+        let url = if config.cli.relative_links {
+            target.relative_to(UrlPath::part("latest").join(&path)) + "/index.html"
+        }
+        else {
+            format!("{}/", root.join(&target))
+        };
+        let dir = latest.join(&page);
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Unable to create directory for latest docs: {e}"))?;
+        fs::write(
+            dir.join("index.html"),
+            format!(
+                "<!DOCTYPE html><html><head><meta charset=\"UTF-8\">\
+                <meta http-equiv=\"refresh\" content=\"0; url={url}\">\
+                <link rel=\"canonical\" href=\"{url}\">\
+                <script>location.replace(\"{url}\" + location.hash);</script>\
+                </head></html>"
+            ),
+        )
+        .map_err(|e| format!("Unable to save latest docs: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Add the version that was just built to `versions.json` and point `latest`
+/// to it if it's the newest version. Other versions' docs are left untouched
+pub fn update_versions(config: &Config) -> Result<(), String> {
+    let Some(ref name) = config.version_dir else {
+        return Ok(());
+    };
+    let path = config.output_root.join("versions.json");
+    let mut manifest = if path.exists() {
+        serde_json::from_str::<VersionManifest>(
+            &fs::read_to_string(&path).map_err(|e| format!("Unable to read versions.json: {e}"))?,
+        )
+        .map_err(|e| format!("Unable to parse versions.json: {e}"))?
+    }
+    else {
+        VersionManifest::default()
+    };
+
+    manifest.versions.retain(|v| &v.name != name);
+    manifest.versions.push(VersionEntry {
+        name: name.clone(),
+        version: config.project.version.clone(),
+        url: config.output_url.clone().unwrap_or(UrlPath::new()).to_string(),
+    });
+    manifest.versions.sort_by(|a, b| compare_versions(&b.version, &a.version));
+    let latest = manifest.versions.first().map(|v| v.name.clone());
+
+    if latest.as_ref() == Some(name) {
+        write_latest_alias(config, name)?;
+    }
+    manifest.latest = latest;
+
+    fs::write(
+        path,
+        serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Unable to save versions.json: {e}"))?,
+    )
+    .map_err(|e| format!("Unable to save versions.json: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_compare_by_number() {
+        assert_eq!(compare_versions("2.0", "10.0"), Ordering::Less);
+        assert_eq!(compare_versions("v3.2.1", "3.10"), Ordering::Less);
+        assert_eq!(compare_versions("1.0", "1.0"), Ordering::Equal);
+    }
+}
@@ -14,30 +14,42 @@ let searchQuery = '';
 
 let memberFunctionsList = null;
 
-function createCopyButton(icon, text, callback = undefined) {
+// Offline docs don't load feather, and instead get the icons they need from
+// Flash as FLASH_ICONS
+function icon(name, cls = undefined) {
+    if (typeof feather !== 'undefined') {
+        return feather.icons[name].toSvg(cls ? { 'class': cls } : {});
+    }
+    const classes = `feather feather-${name}${cls ? ` ${cls}` : ''}`;
+    return `<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 24 24" ` +
+        `fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" ` +
+        `stroke-linejoin="round" class="${classes}">${FLASH_ICONS[name] ?? ''}</svg>`;
+}
+
+function createCopyButton(iconHtml, text, callback = undefined) {
     const button = document.createElement('button');
-    button.innerHTML = `${icon}`;
+    button.innerHTML = iconHtml;
     button.addEventListener('click', _ => {
         if (navigator.clipboard) {
             navigator.clipboard.writeText(text)
                 .then(() => {
-                    button.innerHTML = `${feather.icons.check.toSvg()}`;
+                    button.innerHTML = icon('check');
                     button.classList.add('success');
                     if (callback) {
                         callback();
                     }
                 },
                 () => {
-                    button.innerHTML = `${feather.icons.x.toSvg()}`;
+                    button.innerHTML = icon('x');
                     button.classList.add('failure');
                 });
         }
         else {
-            button.innerHTML = `${feather.icons.x.toSvg()}`;
+            button.innerHTML = icon('x');
             button.classList.add('failure');
         }
         setTimeout(_ => {
-            button.innerHTML = `${icon}`;
+            button.innerHTML = iconHtml;
             button.classList.remove('success');
             button.classList.remove('failure');
         }, 1500);
@@ -46,9 +58,8 @@ function createCopyButton(icon, text, callback = undefined) {
 }
 
 // Add copy button to code blocks
-Prism.hooks.add('complete', env => {
+function addCodeToolbar(pre, code) {
     // Check if inline or actual code block (credit to line-numbers plugin)
-    const pre = env.element.parentNode;
     if (!pre || !/pre/i.test(pre.nodeName)) {
         return;
     }
@@ -71,10 +82,15 @@ Prism.hooks.add('complete', env => {
     wrapper.appendChild(pre);
 
     toolbar.appendChild(createCopyButton(
-        feather.icons.copy.toSvg(),
-        env.code
+        icon('copy'),
+        code
     ));
-});
+}
+
+// Prism is only needed for languages Flash doesn't highlight itself
+if (typeof Prism !== 'undefined') {
+    Prism.hooks.add('complete', env => addCodeToolbar(env.element.parentNode, env.code));
+}
 
 searchInput.addEventListener('input', e => {
     search(e.target.value);
@@ -101,7 +117,7 @@ function highlight() {
                     currentUrl = currentUrl.slice(0, -1)
                 }
                 const linkBtn = createCopyButton(
-                    feather.icons.link.toSvg(),
+                    icon('link'),
                     `${currentUrl}#${head.getAttribute('id')}`,
                     () => {
                         window.location.hash = head.getAttribute('id');
@@ -126,9 +142,19 @@ function highlight() {
             }
         });
 
-    Prism.highlightAll();
-    feather.replace();
-    twemoji.parse(document.body);
+    // Code highlighted by Flash
+    document.querySelectorAll('pre > code.highlighted, pre > code.example')
+        .forEach(code => addCodeToolbar(code.parentNode, code.innerText));
+
+    if (typeof Prism !== 'undefined') {
+        Prism.highlightAll();
+    }
+    if (typeof feather !== 'undefined') {
+        feather.replace();
+    }
+    if (typeof twemoji !== 'undefined') {
+        twemoji.parse(document.body);
+    }
 }
 
 function clearSearch() {
@@ -136,15 +162,26 @@ function clearSearch() {
     search('');
 }
 
+function loadFunctions() {
+    if (!FLASH_RELATIVE_LINKS) {
+        return fetch(`${FLASH_OUTPUT_URL}/functions.json`).then(res => res.json());
+    }
+    // Pages opened from disk can't fetch, so the list is loaded as a script
+    return new Promise(resolve => {
+        const script = document.createElement('script');
+        script.src = `${FLASH_OUTPUT_URL}/functions.js`;
+        script.addEventListener('load', () => resolve(FLASH_FUNCTIONS));
+        document.head.appendChild(script);
+    });
+}
+
 function search(query) {
     searchQuery = query;
     if (!memberFunctionsList && selectedNavTab() == 'entities') {
-        fetch(`${FLASH_OUTPUT_URL}/functions.json`)
-            .then(res => res.json())
-            .then(res => {
-                memberFunctionsList = res;
-                search(searchQuery);
-            });
+        loadFunctions().then(res => {
+            memberFunctionsList = res;
+            search(searchQuery);
+        });
     }
     updateNav();
 }
@@ -284,7 +321,8 @@ function updateNav() {
         }
     
         const results = [];
-        currentNav().querySelectorAll('a').forEach(a => {
+        // links to generated pages aren't entities so skip those
+        currentNav().querySelectorAll('a:not(.pages > a)').forEach(a => {
             const match = furryMatchMany(
                 getFullName(a), searchQuery,
                 selectedNavTab() == 'entities' ? '::' : '/'
@@ -307,15 +345,17 @@ function updateNav() {
                 const match = furryMatchMany([name], searchQuery, '::');
                 if (match) {
                     const node = document.createElement('a');
-                    const url = `${FLASH_OUTPUT_URL}/classes/${f.join('/')}#${name.replace(/\s+\([0-9]+\)/, '')}`;
+                    const page = `${FLASH_OUTPUT_URL}/classes/${f.join('/')}${FLASH_RELATIVE_LINKS ? '/index.html' : ''}`;
+                    const url = `${page}#${name.replace(/\s+\([0-9]+\)/, '')}`;
                     node.setAttribute('href', url);
                     node.addEventListener('click', e => {
-                        navigate(url);
-                        e.preventDefault();
+                        if (!navigate(url)) {
+                            e.preventDefault();
+                        }
                     });
                     f = f.map(a => `<span class="namespace">${a}</span>`);
                     f.push(match.matched);
-                    node.innerHTML = feather.icons.code.toSvg({ 'class': 'icon class' }) + 
+                    node.innerHTML = icon('code', 'icon class') + 
                         f.join('<span class="scope">::</span>');
                     results.push([match.score, node]);
                 }
@@ -384,6 +424,10 @@ function showNav(id) {
 }
 
 function navigate(url) {
+    // Pages opened from disk can't be fetched, so just follow the link
+    if (FLASH_RELATIVE_LINKS) {
+        return true;
+    }
     const trueURL = url.split('#').shift();
     const head = url.split('#').pop();
     Promise.all([
@@ -455,7 +499,9 @@ highlight();
     while (currentUrl.endsWith('/')) {
         currentUrl = currentUrl.slice(0, -1)
     }
-    const a = nav.querySelector(`[href="${currentUrl}"]`);
+    const a = FLASH_RELATIVE_LINKS ?
+        [...nav.querySelectorAll('a')].find(a => a.href === window.location.href.split('#')[0]) :
+        nav.querySelector(`[href="${currentUrl}"]`);
     if (a) {
         // Find the parent nav section of the selected item
         let parentNav = a.closest('.content');
@@ -476,6 +522,32 @@ highlight();
     }
 }
 
+// List every published version in the version switcher. The list is loaded
+// when the page is opened so docs for older versions list newer ones too
+{
+    const switcher = document.getElementById('nav-version');
+    if (switcher) {
+        fetch(switcher.getAttribute('data-versions'))
+            .then(res => res.json())
+            .then(manifest => {
+                const current = switcher.getAttribute('data-current');
+                switcher.innerHTML = '';
+                manifest.versions.forEach(version => {
+                    const option = document.createElement('option');
+                    option.value = version.url;
+                    option.innerText = version.name === manifest.latest ?
+                        `${version.name} (latest)` : version.name;
+                    option.selected = version.name === current;
+                    switcher.appendChild(option);
+                });
+            })
+            .catch(err => console.error(err));
+        switcher.addEventListener('change', () => {
+            window.location.href = `${switcher.value}/`;
+        });
+    }
+}
+
 // Detect header link change
 window.addEventListener('hashchange', () => {
     scrollAndOpenElement(window.location.hash);